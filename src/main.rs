@@ -1,35 +1,36 @@
 use piston_window::*;
 use std::time::{Duration, Instant};
 use std::rc::Rc;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+//the closures the lists, vehicles and upgrades are built from
+type GameFn<T> = Rc<dyn Fn(T, &mut Game)>;
+type Getter<K, T> = Rc<dyn Fn(K, &mut Game) -> T>;
+type IdsFn = Rc<dyn Fn(&mut Game) -> Vec<u32>>;
+type CloneInputsFn = Rc<dyn Fn(&mut Game, String)>;
+type AgentFn = Rc<dyn Fn(&str, &mut Game)>;
+type LevelFn = Rc<dyn Fn(&mut Player, u32)>;
+type CloneFn = Rc<dyn Fn(&mut Player, &Player)>;
+
 //people who might play: 
 //Haelian (yt)
 
 
 struct Menu {
     pressed_space: bool,
-    buttons: ButtonList,
     go: bool,
     quit: bool,
     screen: u8,
     selected_vehicle: u8,
-    artifact1: Artifact,
-    artifact2: Artifact,
-    artifact3: Artifact,
-    health_modifier: u32,
-    damage_modifier: u32,
+    artifact_pool: Vec<Artifact>, //every artifact that can be offered
+    artifact_choices: Vec<Artifact>, //the artifacts offered after the last loop
+    rng: StdRng, //seeded so the same seed offers the same artifacts
     artifacts: Vec<Artifact>,
-    health_boost: u32,
-    damage_boost: u32,
+    last_artifact: String, //the name of the artifact picked after the last loop, so it can be dropped again
     button_screens: ButtonListList,
     vehicles: Vec<Player>,
     keep_old: bool,
-}
-
-struct Run {
-    artifacts: Vec<Artifact>,
-    health_modifier: u32,
-    damage_modifier: u32,
+    swarms: u32, //how many swarms have been sent out, each gets its own id so several clones of it can be around at once
 }
 
 #[derive(Clone)]
@@ -49,8 +50,9 @@ struct TimeLoopStorage {
     max_camera: f64,
     stored_enemies: Vec<Enemy>,
     stored_platforms: Vec<Platform>,
-    end_enemy_cool_down: f64,
-    end_platform_cool_down: f64,
+    stored_segments: Vec<Segment>, //the segments spliced into the run, in order
+    stored_boss_drops: Vec<String>, //what each boss drops, in the order the bosses come in
+    stored_boss_fights: Vec<f64>, //how many updates the camera stopped for each boss, in the order the bosses come in
     playing: bool,
 }
 
@@ -69,7 +71,24 @@ struct Artifact {
     name: String,
     description: String,
     modify_player: Rc<dyn Fn(&mut Player)>,
-    image: u32,
+    stacks: u32, //how many copies of the artifact are owned, each one applies modify_player again
+    max_stacks: u32, //the most copies of the artifact that can be owned
+    affects_clones: bool, //if the artifact is also applied to clones from earlier loops
+    modify_clone: CloneFn, //how the artifact changes a clone, if it affects clones, given the player it runs alongside
+    update_clones: Rc<dyn Fn(&mut Game)>, //run every update while the player has the artifact, to act on the clones
+    rarity: u8, //0 is common, 1 is rare, 2 is legendary
+    weight: f64, //how likely the artifact is to be offered, before rarity is taken into account
+    tags: Vec<String>, //what the artifact is about, used to keep the choices varied
+}
+
+#[derive(Clone)]
+struct Upgrade {
+    name: String,
+    level: u32, //how many times the upgrade has been bought
+    max_level: u32, //the most times the upgrade can be bought
+    cost: u32, //the cost of the first level, later levels cost more
+    requires: String, //the upgrade that has to be bought first, empty if there isn't one
+    modify_player: LevelFn, //apply the upgrade at the given level
 }
 
 #[derive(Clone)]
@@ -80,6 +99,7 @@ struct Keys {
     w: bool,
     special: bool,
     ability: bool,
+    aim: f64, //the angle from the player to the mouse, in radians, 0 is right and pi/2 is up
 }
 
 #[derive(Clone)]
@@ -107,9 +127,123 @@ struct Game {
     tutorial: u8,
     pressed_keys: Keys,
     platforms: PlatformList,
+    pickups: PickupList,
     mouse: Mouse,
     time_loop_storage: TimeLoopStorage,
     time_loop_reading: TimeLoopStorage,
+    loops: u32, //how many loops have been finished
+    currency: u32, //earned from kills, distance and clones, spent on upgrades
+    enemy_types: Vec<EnemyType>, //every kind of enemy that can be spawned
+    bosses: Vec<EnemyType>, //the bosses, in order, min_distance is the milestone each one appears at
+    boss_rewards: u32, //artifacts earned from bosses this run, handed out when the run ends
+    platform_chunks: Vec<Vec<Platform>>, //hand-authored runs of platforms, x is the extra gap before each one and y is the height above the last
+    segments: Vec<Segment>, //hand-authored stretches of level
+    editor: Editor,
+    biomes: Vec<Biome>, //the areas a run goes through, in order
+    particles: Vec<Particle>, //only for show, nothing collides with them
+    damage_numbers: Vec<DamageNumber>, //the damage done by recent hits, floating up from where they landed
+}
+
+#[derive(Clone)]
+struct DamageNumber {
+    x: f64, //position of the number
+    y: f64, //position of the number
+    amount: f64, //the damage done
+    life: f64, //how long until the number disappears
+    colour: [f32; 4], //white for damage to enemies, red for damage to the player and clones
+}
+
+//a speck thrown out by an emitter, fading away as it runs out of life
+#[derive(Clone)]
+struct Particle {
+    x: f64, //position of the particle
+    y: f64, //position of the particle
+    x_velocity: f64, //how fast the particle moves
+    y_velocity: f64, //how fast the particle moves
+    size: f64, //the size of the particle
+    life: f64, //how long until the particle disappears
+    max_life: f64, //the life the particle started with, for fading it out
+    colour: [f32; 4], //the colour of the particle
+    gravity: f64, //how much the particle falls every update
+}
+
+//a burst of particles thrown out when something happens
+#[derive(Clone)]
+struct Emitter {
+    count: u32, //how many particles are thrown out
+    speed: f64, //the fastest a particle is thrown
+    spread: f64, //the angle the particles are thrown over, in radians, centred on the direction given when emitting
+    size: f64, //the biggest a particle can be
+    life: f64, //the longest a particle can last
+    colour: [f32; 4], //the colour of the particles
+    gravity: f64, //how much the particles fall every update
+}
+
+//an area of the run with its own look, enemies and rule
+#[derive(Clone)]
+struct Biome {
+    name: String, //shown across the screen when the biome is reached
+    distance: f64, //how far into the run the biome starts
+    layers: Vec<BackgroundLayer>, //the background, drawn back to front
+    tint: [f32; 4], //the tint drawn over the background
+    platform_images: Vec<u32>, //the platform tile sets new platforms are picked from
+    enemies: Vec<String>, //the enemy types that can spawn here, as long as the run is far enough for them
+    rule: String, //"none", "low gravity", "wind" or "darkness"
+    strength: f64, //how strong the rule is
+}
+
+//one layer of a parallax background, tiled across the screen
+#[derive(Clone)]
+struct BackgroundLayer {
+    image: u32, //which of the background images is used
+    source: String, //"background" or "platform", which list of images it is from
+    rate: f64, //how far the layer scrolls for every step of the camera, far away layers scroll slower
+    y: f64, //the top of the layer on the screen, from the top of the screen
+    height: f64, //how tall the layer is drawn, its width keeps the image's shape
+    colour: [f32; 4], //the tint drawn over the layer, on top of the biome's tint
+}
+
+//the level editor, for laying out a segment with the mouse and trying it straight away
+#[derive(Clone)]
+struct Editor {
+    active: bool, //if the editor is open instead of the menu
+    testing: bool, //if the run is a play-test of the segment, which isn't recorded in the time loop
+    scroll: f64, //how far into the segment the left of the screen is
+    tool: usize, //which of editor_tools is being placed
+    segment: Segment, //the segment being edited
+    drag: Vec<f64>, //where the mouse was pressed while placing a platform, empty if it isn't pressed
+    naming: bool, //if typing changes the segment's name
+    message: String, //what happened the last time the segment was saved
+    kept_storage: TimeLoopStorage, //the real time loop storage, put back once a play-test is over
+}
+
+//a hand-authored stretch of level, spliced into the run at a set distance
+//everything in it is placed by how far into the segment the camera is, so every loop sees it the same way
+#[derive(Clone)]
+struct Segment {
+    name: String,
+    distance: f64, //how far into the run the segment starts
+    length: f64, //how long the segment lasts, nothing random is spawned until it is over
+    random: bool, //if the generator can splice it in anywhere, otherwise it always starts at distance
+    platforms: Vec<Platform>, //x is how far into the segment the platform comes in
+    enemies: Vec<SegmentSpawn>, //name is the enemy type
+    pickups: Vec<SegmentSpawn>, //name is the pickup kind
+    triggers: Vec<Trigger>,
+}
+
+//something that comes in partway through a segment
+#[derive(Clone)]
+struct SegmentSpawn {
+    at: f64, //how far into the segment it comes in, at its left edge for enemies and its middle for pickups
+    name: String,
+    y: f64, //the height it comes in at
+}
+
+//something that happens partway through a segment
+#[derive(Clone)]
+struct Trigger {
+    at: f64, //how far into the segment it happens
+    action: Rc<dyn Fn(&mut Game)>,
 }
 
 #[derive(Clone)]
@@ -117,11 +251,20 @@ struct RandomThings {
     platform_cool_down: f64,
     enemy_cool_down: f64,
     camera_distance: f64,
+    next_boss: usize, //the next boss to appear this run
+    path_y: f64, //the height of the last platform, the next one is placed so it can be reached from it
+    queued_platforms: Vec<Platform>, //the rest of the hand-authored chunk being placed
+    segment: Option<Segment>, //the segment being played, with what it has already placed taken out
+    next_segment: f64, //the distance at which the generator next splices in a segment
+    reach: (f64, f64), //how high and how far the player's vehicle can jump, from its stats at the start of the run
+    camera_stop: f64, //updates left of a recorded boss fight's stop, or -1 while a new boss fight is being recorded
+    fight_time: f64, //how many updates the boss fight being recorded has lasted
 }
 
 #[derive(Clone)]
 struct Action {
     action: String,
+    index: usize, //which artifact choice or upgrade the button is for
 }
 
 #[derive(Clone)]
@@ -139,11 +282,95 @@ struct Player {
     data_num: Vec<f64>, //data used by the player's update function
     moves: KeySequence, //sequence of moves the player has made
     apply_inputs: Rc<dyn Fn(&mut Game)>, //move the player based on the inputs
-    apply_inputs_as_clone: Rc<dyn Fn(&mut Game, String)>, //move the player based on the inputs as a clone
+    apply_inputs_as_clone: CloneInputsFn, //move the player based on the inputs as a clone
     reset: Rc<dyn Fn(&mut Player)>, //reset the player to the starting state
     active: bool, //if the player is currently in the game
     image: u32, //the image of the player
     damage: f64, //how much damage the player does
+    vehicle: u8, //which of the menu's vehicles the player was made from
+    artifacts: Vec<Artifact>, //the artifacts that have been applied to the player
+    loop_made: u32, //which loop the player was recorded in, once it becomes a clone
+    upgrades: Vec<Upgrade>, //the vehicle's upgrade tree
+    weapon: Option<Weapon>, //what the player shoots with, none for vehicles that don't shoot
+    ability: Option<Ability>, //what the player does with the ability key
+    units: Vec<Unit>, //the bodies the player is split into, empty for everything but the swarm
+    buffs: Vec<Buff>, //temporary boosts from pickups
+    invulnerable: f64, //how long until the player can be hurt again after a hit
+    flash: f64, //how long the player is drawn white for after a hit
+    animation: String, //"idle", "driving", "jumping" or "firing"
+    animation_time: f64, //how long the current animation has been playing
+    tilt: f64, //how far the sprite is turned, in radians, positive is nose up
+    max_health: f64, //the health the player starts the run with, after upgrades and artifacts
+    revived: bool, //if the clone has already been brought back this run
+}
+
+//a list of frames played one after the other, all drawn from the vehicle's images
+#[derive(Clone)]
+struct Animation {
+    frames: Vec<Frame>,
+    frame_time: f64, //how long each frame is shown for
+}
+
+#[derive(Clone)]
+struct Frame {
+    rotated: bool, //if the vehicle's tilted image is used, for vehicles that have one
+    x_scale: f64, //how much the sprite is stretched from the vehicle's size
+    y_scale: f64, //how much the sprite is stretched from the vehicle's size
+    x_offset: f64, //how far the sprite is moved from the vehicle's position
+    y_offset: f64, //how far the sprite is moved from the vehicle's position
+}
+
+//one of the small bodies a swarm is made of
+#[derive(Clone)]
+struct Unit {
+    x: f64, //position of the unit
+    y: f64, //position of the unit
+    width: f64, //the size of the unit
+    height: f64, //the size of the unit
+    health: f64, //the unit's share of the swarm's health
+    x_velocity: f64, //how fast the unit is moving
+    y_velocity: f64, //how fast the unit is moving
+    grounded: bool, //if the unit can jump
+}
+
+//a temporary boost from a pickup, undone when it runs out
+#[derive(Clone)]
+struct Buff {
+    name: String,
+    time_left: f64, //how long until the buff wears off
+    end: Rc<dyn Fn(&mut Player)>, //undo what the pickup changed
+}
+
+//what a vehicle shoots with, kept apart from the vehicle so it can be swapped
+#[derive(Clone)]
+struct Weapon {
+    fire_rate: f64, //how long between shots
+    projectile: String, //"bullet" flies straight, "shell" arcs and explodes
+    count: u32, //how many projectiles each shot fires
+    spread: f64, //the angle between the projectiles of a shot, in radians
+    damage_multiplier: f64, //multiplies the damage of the vehicle firing it
+    speed: f64, //how fast the projectiles move
+    blast_radius: f64, //how far a shell's explosion reaches
+    heat_per_shot: f64, //how much heat each shot adds
+    max_heat: f64, //the heat at which the weapon overheats, 0 if it never does
+    cooling: f64, //how much heat is lost every update
+    heat: f64, //how hot the weapon is
+    overheated: bool, //if the weapon has to cool down completely before firing again
+    cool_down: f64, //how long until the weapon can fire again
+}
+
+//an active ability used with the ability key, each vehicle has its own
+#[derive(Clone)]
+struct Ability {
+    cool_down: f64, //how long it takes for a used charge to come back
+    duration: f64, //how long the ability lasts once used, 0 if it happens all at once
+    max_charges: u32, //how many uses can be saved up
+    charges: u32, //how many uses are saved up
+    recharge: f64, //how long until the next charge comes back
+    time_left: f64, //how long the current use has left
+    held: bool, //if the ability key was held last update, so holding it only uses one charge
+    start: AgentFn, //what happens when the ability is used, given who used it, "player" for the player and the clone's id otherwise
+    end: AgentFn, //undo what start changed once the duration is over
 }
 
 #[derive(Clone)]
@@ -153,12 +380,11 @@ struct Bullet {
     width: f64, //the size of the bullet
     height: f64, //the size of the bullet
     speed: f64, //how fast the bullet moves
-    direction: f64, //the direction the bullet is moving
+    direction: f64, //the direction the bullet is moving, in radians, 0 is right and pi/2 is up
     damage: f64, //how much damage the bullet does
-    data_bool: Vec<bool>, //data used by the bullet's update function
     data_string: Vec<String>, //data used by the bullet's update function
     data_num: Vec<f64>, //data used by the bullet's update function
-    update: Rc<dyn Fn(u32, &mut Game)>, //update the bullet based on the game state
+    update: GameFn<u32>, //update the bullet based on the game state
     id: u32, //the id of the bullet
     image: u32, //the image of the bullet
 }
@@ -171,38 +397,75 @@ struct Enemy {
     height: f64, //the size of the enemy
     health: f64, //how much health the enemy has
     speed: f64, //how fast the enemy moves
-    data_bool: Vec<bool>, //data used by the enemy's update function
-    data_string: Vec<String>, //data used by the enemy's update function
+    last_hit_by: String, //"player" or "clone", whichever hit the enemy last, so its kill is paid out to the right one
     data_num: Vec<f64>, //data used by the enemy's update function
-    update: Rc<dyn Fn(u32, &mut Game)>, //update the enemy based on the game state
+    update: GameFn<u32>, //update the enemy based on the game state
     id: u32, //the id of the enemy
     image: u32, //the image of the enemy
     shoot_cool_down: f64, //how long until the enemy can shoot again
+    colour: [f32; 4], //the tint drawn over the enemy's image
+    max_health: f64, //the health the enemy started with, for the boss health bar and phases
+    boss: bool, //if the enemy is a boss, which stops the camera until it is beaten
+    patterns: Vec<BulletPattern>, //the ways the enemy can shoot, bosses use one per phase
+    drop: String, //the pickup the enemy leaves when it dies, empty for none, picked on spawn so replays drop the same things
+}
+
+//a description of how an enemy shoots, so enemy types and bosses can share attacks
+#[derive(Clone)]
+struct BulletPattern {
+    kind: String, //"spread", "spiral", "aimed", "homing" or "rain"
+    count: u32, //how many bullets are fired each volley
+    spread: f64, //the angle between the bullets in a volley, in radians
+    direction: f64, //the direction a spread or homing volley is centred on, pi is left
+    speed: f64, //how fast the bullets move
+    damage: f64, //how much damage each bullet does
+    size: f64, //the size of the bullets
+    cool_down: f64, //how long between volleys
+    turn: f64, //how far a spiral or rain turns each volley, or how fast homing bullets turn
+    delay: f64, //how long homing bullets fly straight before they start homing
+    angle: f64, //how far a spiral or rain has turned so far
+}
+
+//a kind of enemy that can be spawned, with its own stats and behaviour
+#[derive(Clone)]
+struct EnemyType {
+    name: String,
+    width: f64, //the size of the enemy
+    height: f64, //the size of the enemy
+    health: f64, //how much health the enemy starts with
+    speed: f64, //how fast the enemy moves
+    image: u32, //the image of the enemy
+    colour: [f32; 4], //the tint drawn over the image, so enemies sharing an image look different
+    weight: f64, //how likely the enemy is to be picked by the spawn table
+    min_distance: f64, //how far into the run the enemy can start spawning
+    data_num: Vec<f64>, //starting data for the enemy's update function
+    patterns: Vec<BulletPattern>, //the ways the enemy can shoot, bosses use one per phase
+    update: GameFn<u32>, //update the enemy based on the game state
 }
 
 #[derive(Clone)]
 struct PlayerList {
     players: Vec<Player>,
-    add: Rc<dyn Fn(Player, &mut Game)>,
-    get: Rc<dyn Fn(String, &mut Game) -> Player>,
-    remove: Rc<dyn Fn(String, &mut Game)>,
+    add: GameFn<Player>,
+    get: Getter<String, Player>,
+    remove: GameFn<String>,
 }
 
 #[derive(Clone)]
 struct BulletList {
     bullets: Vec<Bullet>,
-    add: Rc<dyn Fn(Bullet, &mut Game)>,
-    get: Rc<dyn Fn(u32, &mut Game) -> Bullet>,
-    remove: Rc<dyn Fn(u32, &mut Game)>,
+    add: GameFn<Bullet>,
+    get: Getter<u32, Bullet>,
+    remove: GameFn<u32>,
 }
 
 #[derive(Clone)]
 struct EnemyList {
     enemies: Vec<Enemy>,
-    add: Rc<dyn Fn(Enemy, &mut Game)>,
-    get: Rc<dyn Fn(u32, &mut Game) -> Enemy>,
-    remove: Rc<dyn Fn(u32, &mut Game)>,
-    get_ids: Rc<dyn Fn(&mut Game) -> Vec<u32>>,
+    add: GameFn<Enemy>,
+    get: Getter<u32, Enemy>,
+    remove: GameFn<u32>,
+    get_ids: IdsFn,
 }
 
 #[derive(Clone)]
@@ -213,89 +476,334 @@ struct Platform {
     height: f64, //the size of the platform
     id: u32, //the id of the platform
     image: u32, //the image of the platform
+    pickup: String, //the pickup sitting on the platform when it comes in, empty for none
+    kind: String, //"plain", "moving", "crumbling", "one way" or "bouncy"
+    x_velocity: f64, //how far the platform moved sideways this update, so things standing on it move with it
+    data_num: Vec<f64>, //data used by the platform's kind
+}
+
+//something lying around that the player or a clone can collect by touching it
+#[derive(Clone)]
+struct Pickup {
+    x: f64, //position of the pickup
+    y: f64, //position of the pickup
+    width: f64, //the size of the pickup
+    height: f64, //the size of the pickup
+    kind: String, //"repair", "coolant", "currency", "overdrive" or "haste"
+    y_velocity: f64, //how fast the pickup is falling
+    id: u32, //the id of the pickup
+}
+
+#[derive(Clone)]
+struct PickupList {
+    pickups: Vec<Pickup>,
+    add: GameFn<Pickup>,
+    remove: GameFn<u32>,
 }
 
 #[derive(Clone)]
 struct PlatformList {
     platforms: Vec<Platform>,
-    add: Rc<dyn Fn(Platform, &mut Game)>,
-    get: Rc<dyn Fn(u32, &mut Game) -> Platform>,
-    remove: Rc<dyn Fn(u32, &mut Game)>,
+    add: GameFn<Platform>,
+    remove: GameFn<u32>,
 }
 
 
 
-fn update_clone(agent_id: String, mut state: &mut Game) {
+fn update_clone(agent_id: String, state: &mut Game) {
     let get = state.clones.get.clone();
-    ((get)(agent_id.clone(), &mut state).apply_inputs_as_clone)(&mut state, agent_id);
+    //the ability goes first, reading the same step of the recording as the movement
+    let clone = (get)(agent_id.clone(), state);
+    update_ability(&agent_id, clone.moves.sequence[clone.moves.step as usize].ability, state);
+    ((get)(agent_id.clone(), state).apply_inputs_as_clone)(state, agent_id);
 }
 
-fn update_player(mut state: &mut Game) {
-    add_inputs(get_inputs(&mut state), &mut state.player);
+fn update_player(state: &mut Game) {
+    add_inputs(get_inputs(state), &mut state.player);
+    update_player_ability(state);
     let apply_inputs = state.player.apply_inputs.clone();
-    (apply_inputs)(&mut state);
+    (apply_inputs)(state);
+}
+
+fn tick_ability(ability: &mut Ability, pressed: bool) -> (bool, bool) {
+    //count down the current use and the next charge, then use a charge if the key has just been pressed
+    //returns if the ability started and if it ended this update
+    let mut ended = false;
+    if ability.time_left > 0.0 {
+        ability.time_left -= 1.0/100.0;
+        if ability.time_left <= 0.0 {
+            ended = true;
+        }
+    }
+    if ability.charges < ability.max_charges {
+        ability.recharge -= 1.0/100.0;
+        if ability.recharge <= 0.0 {
+            ability.charges += 1;
+            ability.recharge = ability.cool_down;
+        }
+    }
+    let started = pressed && !ability.held && ability.charges > 0 && ability.time_left <= 0.0 && !ended;
+    ability.held = pressed;
+    if started {
+        if ability.charges == ability.max_charges {
+            ability.recharge = ability.cool_down;
+        }
+        ability.charges -= 1;
+        ability.time_left = ability.duration;
+        if ability.duration <= 0.0 {
+            ended = true;
+        }
+    }
+    (started, ended)
+}
+
+fn update_ability(target: &str, pressed: bool, state: &mut Game) {
+    //the ability is taken out of the agent while it runs, so it can change both the agent and the game
+    let ability = agent_mut(target, state).and_then(|agent| agent.ability.take());
+    if let Some(mut ability) = ability {
+        let (started, ended) = tick_ability(&mut ability, pressed);
+        if started {
+            (ability.start)(target, state);
+        }
+        if ended {
+            (ability.end)(target, state);
+        }
+        if let Some(agent) = agent_mut(target, state) {
+            agent.ability = Some(ability);
+        }
+    }
+}
+
+fn update_player_ability(state: &mut Game) {
+    let pressed = state.player.moves.sequence[state.player.moves.step as usize].ability;
+    update_ability("player", pressed, state);
+}
+
+fn credit(target: &str) -> String {
+    //who gets paid for kills made by an agent's ability
+    if target == "player" {
+        "player".to_string()
+    } else {
+        "clone".to_string()
+    }
+}
+
+fn ability(name: &str) -> Ability {
+    //the abilities the vehicles use, by name
+    let nothing: AgentFn = Rc::new(|_target: &str, _state: &mut Game| {});
+    let blank = Ability {cool_down: 1.0, duration: 0.0, max_charges: 1, charges: 1, recharge: 0.0, time_left: 0.0, held: false, start: nothing.clone(), end: nothing};
+    match name {
+        "dash" => Ability {
+            cool_down: 2.0,
+            max_charges: 3,
+            charges: 3,
+            start: Rc::new(|target: &str, state: &mut Game| {
+                if let Some(agent) = agent_mut(target, state) {
+                    let aim = agent.moves.sequence[agent.moves.step as usize].aim;
+                    agent.data_num[0] += aim.cos()*30.0;
+                    agent.data_num[1] += aim.sin()*30.0;
+                }
+            }),
+            ..blank
+        },
+        "shockwave" => Ability {
+            cool_down: 6.0,
+            start: Rc::new(|target: &str, state: &mut Game| {
+                let credit = credit(target);
+                if let Some((x, y)) = agent_position(target, state) {
+                    for enemy in state.enemies.enemies.iter_mut() {
+                        if (enemy.x - x).abs() < 300.0 && (enemy.y - y).abs() < 300.0 {
+                            enemy.health -= 5.0;
+                            enemy.last_hit_by = credit.clone();
+                        }
+                    }
+                }
+            }),
+            ..blank
+        },
+        "barrage" => Ability {
+            cool_down: 12.0,
+            duration: 3.0,
+            start: Rc::new(|target: &str, state: &mut Game| {
+                if let Some(weapon) = agent_mut(target, state).and_then(|agent| agent.weapon.as_mut()) {
+                    weapon.fire_rate /= 4.0;
+                }
+            }),
+            end: Rc::new(|target: &str, state: &mut Game| {
+                if let Some(weapon) = agent_mut(target, state).and_then(|agent| agent.weapon.as_mut()) {
+                    weapon.fire_rate *= 4.0;
+                }
+            }),
+            ..blank
+        },
+        "rally" => Ability {
+            cool_down: 10.0,
+            duration: 4.0,
+            start: Rc::new(|target: &str, state: &mut Game| {
+                if let Some(agent) = agent_mut(target, state) {
+                    agent.speed *= 1.5;
+                    agent.damage *= 1.5;
+                }
+            }),
+            end: Rc::new(|target: &str, state: &mut Game| {
+                if let Some(agent) = agent_mut(target, state) {
+                    agent.speed /= 1.5;
+                    agent.damage /= 1.5;
+                }
+            }),
+            ..blank
+        },
+        "nitro boost" => Ability {
+            cool_down: 8.0,
+            duration: 2.0,
+            start: Rc::new(|target: &str, state: &mut Game| {
+                if let Some(agent) = agent_mut(target, state) {
+                    agent.speed *= 2.0;
+                }
+            }),
+            end: Rc::new(|target: &str, state: &mut Game| {
+                if let Some(agent) = agent_mut(target, state) {
+                    agent.speed /= 2.0;
+                }
+            }),
+            ..blank
+        },
+        "barrier" => Ability {
+            cool_down: 8.0,
+            max_charges: 2,
+            charges: 2,
+            start: Rc::new(|target: &str, state: &mut Game| {
+                if let Some((x, y)) = agent_position(target, state) {
+                    for bullet in state.enemy_bullets.bullets.clone() {
+                        if (bullet.x - x).abs() < 250.0 && (bullet.y - y).abs() < 250.0 {
+                            let remove = state.enemy_bullets.remove.clone();
+                            (remove)(bullet.id, state);
+                        }
+                    }
+                }
+                if let Some(agent) = agent_mut(target, state) {
+                    agent.health += 20.0;
+                }
+            }),
+            ..blank
+        },
+        _ => blank,
+    }
+}
+
+fn next_id(ids: impl Iterator<Item = u32>) -> u32 {
+    //the lowest ID that isn't already used
+    let used_ids = ids.collect::<Vec<u32>>();
+    let mut new_id = 0;
+    while used_ids.contains(&new_id) {
+        new_id += 1;
+    }
+    new_id
 }
 
-fn update_player_bullet(bullet_id: u32, mut state: &mut Game) {
+fn update_player_bullet(bullet_id: u32, state: &mut Game) {
     let get = state.player_bullets.get.clone();
     ((get)(bullet_id, state).update)(bullet_id, state);
 }
 
-fn update_enemy_bullet(bullet_id: u32, mut state: &mut Game) {
+fn update_enemy_bullet(bullet_id: u32, state: &mut Game) {
     let get = state.enemy_bullets.get.clone();
     ((get)(bullet_id, state).update)(bullet_id, state);
 }
 
-fn update_players(mut state: &mut Game) {
+fn update_players(state: &mut Game) {
     for clone in state.clones.players.clone() {
-        update_clone(clone.id, &mut state);
+        if clone.active {
+            update_clone(clone.id, state);
+        }
+    }
+    //wear down the buffs from pickups, and the time since the last hit
+    update_buffs(&mut state.player);
+    for clone in state.clones.players.iter_mut().filter(|clone| clone.active) {
+        update_buffs(clone);
     }
+    //let the player's artifacts act on the clones
+    for artifact in state.player.artifacts.clone() {
+        (artifact.update_clones)(state);
+    }
+
+    update_player(state);
 
-    update_player(&mut state);
+    //then the biome acts on everyone, and their animations catch up with what they did
+    let biome = current_biome(state);
+    apply_biome_rule(&mut state.player, &biome);
+    update_animation(&mut state.player);
+    for clone in state.clones.players.iter_mut().filter(|clone| clone.active) {
+        apply_biome_rule(clone, &biome);
+        update_animation(clone);
+    }
 }
 
-fn update_bullets(mut state: &mut Game) {
+fn update_bullets(state: &mut Game) {
     for bullet in state.enemy_bullets.bullets.clone() {
-        update_enemy_bullet(bullet.id, &mut state);
+        update_enemy_bullet(bullet.id, state);
     }
 
     for bullet in state.player_bullets.bullets.clone() {
-        update_player_bullet(bullet.id, &mut state);
+        update_player_bullet(bullet.id, state);
     }
 
     //remove bullets that are off screen
     let mut remove_ids = vec![];
     for bullet in state.enemy_bullets.bullets.iter() {
-        if bullet.x + bullet.width/2.0 < 0.0 {
-            remove_ids.push(bullet.id);
-        } else if bullet.x - bullet.width/2.0 > 1440.0 {
+        //leave room above the screen for shells to arc back down
+        if bullet.x + bullet.width/2.0 < 0.0 || bullet.x - bullet.width/2.0 > 1440.0 || bullet.y + bullet.height/2.0 < 0.0 || bullet.y - bullet.height/2.0 > 1800.0 {
             remove_ids.push(bullet.id);
         }
     }
     for id in remove_ids {
         let remove = state.enemy_bullets.remove.clone();
-        (remove)(id, &mut state);
+        (remove)(id, state);
     }
     remove_ids = vec![];
     for bullet in state.player_bullets.bullets.iter() {
-        if bullet.x + bullet.width/2.0 < 0.0 {
-            remove_ids.push(bullet.id);
-        } else if bullet.x - bullet.width/2.0 > 1440.0 {
+        //leave room above the screen for shells to arc back down
+        if bullet.x + bullet.width/2.0 < 0.0 || bullet.x - bullet.width/2.0 > 1440.0 || bullet.y + bullet.height/2.0 < 0.0 || bullet.y - bullet.height/2.0 > 1800.0 {
             remove_ids.push(bullet.id);
         }
     }
     for id in remove_ids {
         let remove = state.player_bullets.remove.clone();
-        (remove)(id, &mut state);
+        (remove)(id, state);
+    }
+}
+
+fn hit_agent(agent: &mut Player, x: f64, y: f64, width: f64, height: f64, damage: f64, numbers: &mut Vec<DamageNumber>) -> bool {
+    //damage the agent if the box touches it, a swarm is hit one unit at a time
+    //for a moment after a hit the agent can't be hurt, but still blocks what hits it
+    let damage = if agent.invulnerable > 0.0 {0.0} else {damage};
+    let mut hit = false;
+    if agent.units.is_empty()
+        && x + width/2.0 > agent.x - agent.width/2.0 && x - width/2.0 < agent.x + agent.width/2.0 && y + height/2.0 > agent.y - agent.height/2.0 && y - height/2.0 < agent.y + agent.height/2.0 {
+            agent.health -= damage;
+            hit = true;
+        }
+    for unit in agent.units.iter_mut() {
+        if x + width/2.0 > unit.x - unit.width/2.0 && x - width/2.0 < unit.x + unit.width/2.0 && y + height/2.0 > unit.y - unit.height/2.0 && y - height/2.0 < unit.y + unit.height/2.0 {
+            unit.health -= damage;
+            agent.health -= damage;
+            hit = true;
+            break;
+        }
     }
+    if hit && damage > 0.0 {
+        agent.invulnerable = 0.5;
+        agent.flash = 0.15;
+        numbers.push(DamageNumber {x, y, amount: damage, life: 0.8, colour: [1.0, 0.2, 0.2, 1.0]});
+    }
+    hit
 }
 
-fn check_hits(mut state: &mut Game) {
+fn check_hits(state: &mut Game) {
     for bullet in state.enemy_bullets.bullets.clone() {
-        if bullet.x + 0.1 > state.player.x - state.player.width/2.0 && bullet.x - 0.1 < state.player.x + state.player.width/2.0 && bullet.y + 0.1 > state.player.y - state.player.height/2.0 && bullet.y - 0.1 < state.player.y + state.player.height/2.0 {
-            state.player.health -= bullet.damage;
+        if hit_agent(&mut state.player, bullet.x, bullet.y, 0.2, 0.2, bullet.damage, &mut state.damage_numbers) {
             let remove = state.enemy_bullets.remove.clone();
-            (remove)(bullet.id, &mut state);
+            (remove)(bullet.id, state);
         }
     }
 
@@ -304,22 +812,22 @@ fn check_hits(mut state: &mut Game) {
             if bullet.x + 0.1 > enemy.x - 0.1 && bullet.x - 0.1 < enemy.x + 0.1 && bullet.y + 0.1 > enemy.y - 0.1 && bullet.y - 0.1 < enemy.y + 0.1 {
                 enemy.health -= bullet.damage;
                 let remove = state.player_bullets.remove.clone();
-                (remove)(bullet.id, &mut state);
+                (remove)(bullet.id, state);
             }
         }
     }
 }
 
-fn check_deaths(mut state: &mut Game) {
+fn check_deaths(state: &mut Game) {
     for clone in state.clones.players.clone() {
-        if check_death(&clone) {
-            kill(clone.id, &mut state);
+        if clone.active && check_death(&clone) {
+            kill(clone.id, state);
         }
     }
     for enemy in state.enemies.enemies.clone() {
         if enemy.health <= 0.0 {
             let remove = state.enemies.remove.clone();
-            (remove)(enemy.id, &mut state);
+            (remove)(enemy.id, state);
         }
     }
 }
@@ -328,16 +836,70 @@ fn check_death(agent: &Player) -> bool {
     agent.health <= 0.0
 }
 
-fn end_run(mut state: &mut Game) {
+fn end_run(state: &mut Game) {
     state.in_run = false;
 
-    make_clone(state.player.clone(), &mut state);
+    make_clone(state.player.clone(), state);
+    state.loops += 1;
+
+    //pay out for the distance travelled, and for every clone that made it this far
+    state.currency += (state.random_things.camera_distance/100.0) as u32;
+    state.currency += 2*state.clones.players.iter().filter(|clone| clone.active).count() as u32;
+    //charge left in the player's generator is sold off
+    if state.player.id == "Generator" {
+        state.currency += (state.player.data_num[4]/5.0) as u32;
+    }
 
     //put things in the time loop storage
     state.time_loop_storage.max_camera = state.time_loop_storage.max_camera.max(state.random_things.camera_distance);
 }
 
-fn do_button(button: Action, mut menu: &mut Menu) {
+fn upgrade_cost(upgrade: &Upgrade) -> u32 {
+    //every level costs half again as much as the last one
+    (upgrade.cost as f64 * 1.5f64.powi(upgrade.level as i32)) as u32
+}
+
+fn can_upgrade(upgrades: &[Upgrade], i: usize) -> bool {
+    //an upgrade can be bought if it isn't maxed out and the upgrade before it in the tree has been bought
+    let upgrade = &upgrades[i];
+    upgrade.level < upgrade.max_level && (upgrade.requires.is_empty() || upgrades.iter().any(|other| other.name == upgrade.requires && other.level > 0))
+}
+
+fn apply_upgrades(agent: &mut Player) {
+    for upgrade in agent.upgrades.clone() {
+        if upgrade.level > 0 {
+            (upgrade.modify_player)(agent, upgrade.level);
+        }
+    }
+}
+
+fn collect_kills(state: &mut Game) {
+    //pay out for enemies killed since the last update, clones earn a bit less than the player
+    for enemy in state.enemies.enemies.clone() {
+        //leave behind whatever the enemy was carrying
+        if enemy.health <= 0.0 && !enemy.drop.is_empty() {
+            spawn_pickup(&enemy.drop, enemy.x, enemy.y, state);
+        }
+        //break apart in the enemy's colour, bosses more so
+        if enemy.health <= 0.0 {
+            let count = if enemy.boss {150} else {20};
+            emit_particles(&Emitter {count, colour: enemy.colour, size: enemy.width.min(enemy.height)/4.0, ..emitter("debris")}, enemy.x, enemy.y, 0.0, state);
+        }
+        if enemy.health <= 0.0 && enemy.boss {
+            //bosses pay out no matter who lands the last hit, and give an artifact at the end of the run
+            state.currency += 50;
+            state.boss_rewards += 1;
+        } else if enemy.health <= 0.0 {
+            if enemy.last_hit_by == "player" {
+                state.currency += 5;
+            } else if enemy.last_hit_by == "clone" {
+                state.currency += 3;
+            }
+        }
+    }
+}
+
+fn do_button(button: Action, menu: &mut Menu, state: &mut Game) {
     if button.action == "replay" {
         menu.go = true;
         menu.keep_old = true;
@@ -352,19 +914,34 @@ fn do_button(button: Action, mut menu: &mut Menu) {
         menu.screen = 3;
     } else if button.action == "back" {
         menu.screen = 0;
-    } else if button.action == "artifact1" {
-        menu.artifacts.push(menu.artifact1.clone());
-        menu.screen = 1;
-    } else if button.action == "artifact2" {
-        menu.artifacts.push(menu.artifact2.clone());
-        menu.screen = 1;
-    } else if button.action == "artifact3" {
-        menu.artifacts.push(menu.artifact3.clone());
-        menu.screen = 1;
-    } else if button.action == "health" {
-        menu.health_modifier += menu.health_boost;
-    } else if button.action == "damage" {
-        menu.damage_modifier += menu.damage_boost;
+    } else if button.action == "artifact" {
+        //the index is which of the choices was picked
+        let choice = button.index;
+        if choice < menu.artifact_choices.len() {
+            menu.last_artifact = menu.artifact_choices[choice].name.clone();
+            add_artifact(menu.artifact_choices[choice].clone(), menu);
+            menu.screen = 1;
+        }
+    } else if button.action == "drop artifact" {
+        //give back the artifact that was just picked and choose again
+        if !menu.last_artifact.is_empty() {
+            remove_artifact(menu.last_artifact.clone(), menu);
+            menu.last_artifact = "".to_string();
+        }
+        menu.screen = 0;
+    } else if button.action == "upgrade" {
+        //the index is which upgrade in the tree of the vehicle being replayed
+        let i = button.index;
+        let upgrades = &mut menu.vehicles[state.player.vehicle as usize].upgrades;
+        if i < upgrades.len() && can_upgrade(upgrades, i) {
+            let cost = upgrade_cost(&upgrades[i]);
+            if state.currency >= cost {
+                state.currency -= cost;
+                upgrades[i].level += 1;
+            }
+        }
+    } else if button.action == "editor" {
+        state.editor.active = true;
     } else if button.action == "next vehicle" {
         if menu.selected_vehicle == 5 {
             menu.selected_vehicle = 0;
@@ -380,7 +957,130 @@ fn do_button(button: Action, mut menu: &mut Menu) {
     }
 }
 
-fn kill(clone_id: String, mut state: &mut Game) {
+fn rarity_weight(rarity: u8) -> f64 {
+    match rarity {
+        0 => 1.0,
+        1 => 0.35,
+        _ => 0.1,
+    }
+}
+
+fn draw_artifacts(menu: &mut Menu) {
+    menu.artifact_choices = roll_artifacts(menu, 3);
+}
+
+fn roll_artifacts(menu: &mut Menu, count: usize) -> Vec<Artifact> {
+    //pick different artifacts from the pool, skipping owned ones that can't stack any higher
+    let mut candidates = vec![];
+    for artifact in menu.artifact_pool.iter() {
+        let maxed = menu.artifacts.iter().any(|owned| owned.name == artifact.name && owned.stacks >= owned.max_stacks);
+        if !maxed {
+            candidates.push(artifact.clone());
+        }
+    }
+    let mut picks: Vec<Artifact> = vec![];
+    while picks.len() < count && !candidates.is_empty() {
+        //artifacts that share a tag with one already picked are less likely, so the choices differ
+        let weights = candidates.iter().map(|artifact| {
+            let shared = artifact.tags.iter().any(|tag| picks.iter().any(|choice| choice.tags.contains(tag)));
+            artifact.weight * rarity_weight(artifact.rarity) * if shared {0.5} else {1.0}
+        }).collect::<Vec<f64>>();
+        let total: f64 = weights.iter().sum();
+        let mut roll = menu.rng.gen_range(0.0..total);
+        let mut pick = candidates.len() - 1;
+        for (i, weight) in weights.iter().enumerate() {
+            if roll < *weight {
+                pick = i;
+                break;
+            }
+            roll -= weight;
+        }
+        picks.push(candidates.remove(pick));
+    }
+    picks
+}
+
+fn add_artifact(artifact: Artifact, menu: &mut Menu) {
+    //owning the same artifact again adds a stack, up to its max_stacks
+    for owned in menu.artifacts.iter_mut() {
+        if owned.name == artifact.name {
+            if owned.stacks < owned.max_stacks {
+                owned.stacks += 1;
+            }
+            return;
+        }
+    }
+    let mut artifact = artifact;
+    artifact.stacks = 1;
+    menu.artifacts.push(artifact);
+}
+
+fn remove_artifact(name: String, menu: &mut Menu) {
+    //remove one stack of the artifact, and the artifact itself once it has none left
+    for (i, owned) in menu.artifacts.clone().into_iter().enumerate() {
+        if owned.name == name {
+            if owned.stacks > 1 {
+                menu.artifacts[i].stacks -= 1;
+            } else {
+                menu.artifacts.remove(i);
+            }
+            return;
+        }
+    }
+}
+
+fn restore_stats(agent: &mut Player, vehicles: &[Player]) {
+    agent.weapon = vehicles[agent.vehicle as usize].weapon.clone();
+    agent.ability = vehicles[agent.vehicle as usize].ability.clone();
+    agent.buffs = vec![];
+    //start from the stats of the vehicle the agent was made from, so artifacts and upgrades don't build up over replays
+    let base = &vehicles[agent.vehicle as usize];
+    agent.health = base.health;
+    agent.speed = base.speed;
+    agent.jump = base.jump;
+    agent.damage = base.damage;
+}
+
+fn apply_artifacts(artifacts: &[Artifact], agent: &mut Player, vehicles: &[Player]) {
+    restore_stats(agent, vehicles);
+    apply_upgrades(agent);
+    for artifact in artifacts.iter() {
+        for _ in 0..artifact.stacks {
+            (artifact.modify_player)(agent);
+        }
+    }
+    agent.artifacts = artifacts.to_vec();
+    agent.max_health = agent.health;
+}
+
+fn apply_clone_artifacts(artifacts: &[Artifact], clone: &mut Player, player: &Player, vehicles: &[Player]) {
+    //clones get back the upgrades and artifacts they were recorded with
+    restore_stats(clone, vehicles);
+    apply_upgrades(clone);
+    for artifact in clone.artifacts.clone() {
+        for _ in 0..artifact.stacks {
+            (artifact.modify_player)(clone);
+        }
+    }
+    //then every artifact that affects clones, whether it was owned when the clone was recorded or since
+    let mut clone_artifacts = clone.artifacts.clone();
+    for artifact in artifacts.iter() {
+        if !clone_artifacts.iter().any(|owned| owned.name == artifact.name) {
+            clone_artifacts.push(artifact.clone());
+        }
+    }
+    for artifact in clone_artifacts.iter() {
+        if artifact.affects_clones {
+            for _ in 0..artifact.stacks {
+                (artifact.modify_clone)(clone, player);
+            }
+        }
+    }
+    clone.max_health = clone.health;
+    clone.revived = false;
+}
+
+fn kill(clone_id: String, state: &mut Game) {
     //set clone to inactive
     let get = state.clones.get.clone();
     let mut clone = (get)(clone_id.clone(), state);
@@ -414,41 +1114,434 @@ fn add_inputs(inputs: Keys, player: &mut Player) {
 }
 
 fn get_inputs(state: &Game) -> Keys {
-    state.pressed_keys.clone()
+    //the aim is stored with the keys so clones shoot where the player was aiming
+    let mut inputs = state.pressed_keys.clone();
+    inputs.aim = ((900.0 - state.mouse.y) - state.player.y).atan2(state.mouse.x - state.player.x);
+    inputs
 }
 
-fn update_camera(mut state: &mut Game) {
-    for clone in state.clones.players.iter_mut() {
-        clone.x -= 1.0;
+fn collide_platforms(agent: &mut Player, platforms: &[Platform]) {
+    //push the agent out of every platform it overlaps the shortest way, landing on top of one lets it jump again
+    for platform in platforms.iter() {
+        if (agent.x - platform.x).abs() < platform.width/2.0 + agent.width/2.0 && (agent.y - platform.y).abs() < platform.height/2.0 + agent.height/2.0 {
+            //check which side of the platform is closest
+            let left_overlap = (agent.x + agent.width/2.0) - (platform.x - platform.width/2.0);
+            let right_overlap = (platform.x + platform.width/2.0) - (agent.x - agent.width/2.0);
+            let bottom_overlap = (agent.y + agent.height/2.0) - (platform.y - platform.height/2.0);
+            let top_overlap = (platform.y + platform.height/2.0) - (agent.y - agent.height/2.0);
+            //find the smallest overlap
+            let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
+            //one way platforms only stop things landing on them from above
+            let passes_through = platform.kind == "one way" && (smallest_overlap != top_overlap || agent.data_num[1] > 0.0);
+            if passes_through {
+                continue;
+            }
+            //apply the smallest overlap
+            if smallest_overlap == left_overlap {
+                agent.data_num[0] = agent.data_num[0].min(0.0);
+                agent.x = platform.x - platform.width/2.0 - agent.width/2.0 + 1.0;
+            } else if smallest_overlap == right_overlap {
+                agent.data_num[0] = agent.data_num[0].max(0.0);
+                agent.x = platform.x + platform.width/2.0 + agent.width/2.0 - 1.0;
+            } else if smallest_overlap == top_overlap {
+                agent.data_num[1] = agent.data_num[1].max(0.0);
+                agent.y = platform.y + platform.height/2.0 + agent.height/2.0 - 1.0;
+                agent.data_bool[2] = true;
+                //ride along with moving platforms, and get thrown back up by bouncy ones
+                agent.x += platform.x_velocity;
+                if platform.kind == "bouncy" {
+                    agent.data_num[1] = agent.jump*1.3;
+                }
+            } else if smallest_overlap == bottom_overlap {
+                agent.data_num[1] = agent.data_num[1].min(0.0);
+                agent.y = platform.y - platform.height/2.0 - agent.height/2.0 + 1.0;
+            }
+        }
     }
-    for bullet in state.player_bullets.bullets.iter_mut() {
-        bullet.x -= 1.0;
+}
+
+fn update_units(agent: &mut Player, inputs: &Keys, platforms: &[Platform]) {
+    //every unit in a swarm follows the same inputs, but moves and collides on its own
+    //the swarm's health is split between the units, anything that changed the whole swarm's health is shared out
+    let unit_health: f64 = agent.units.iter().map(|unit| unit.health).sum();
+    if agent.units.is_empty() && agent.health > 0.0 {
+        //data_num[3] is how many units the swarm starts with
+        let count = (agent.data_num[3] as usize).max(1);
+        for i in 0..count {
+            agent.units.push(Unit {x: agent.x + i as f64*30.0, y: agent.y, width: 25.0, height: 30.0, health: agent.health/count as f64, x_velocity: 0.0, y_velocity: 0.0, grounded: false});
+        }
+    } else if !agent.units.is_empty() && (agent.health - unit_health).abs() > 0.001 {
+        let share = (agent.health - unit_health)/agent.units.len() as f64;
+        for unit in agent.units.iter_mut() {
+            unit.health += share;
+        }
     }
-    for bullet in state.enemy_bullets.bullets.iter_mut() {
-        bullet.x -= 1.0;
+    //lose the units that have been destroyed
+    agent.units.retain(|unit| unit.health > 0.0);
+    if agent.units.is_empty() {
+        agent.health = 0.0;
+        return;
     }
-    for enemy in state.enemies.enemies.iter_mut() {
-        enemy.x -= 1.0;
+    let centre = agent.units.iter().map(|unit| unit.x).sum::<f64>()/agent.units.len() as f64;
+    let others = agent.units.clone();
+    for (i, unit) in agent.units.iter_mut().enumerate() {
+        if inputs.a {
+            unit.x_velocity -= agent.speed;
+        }
+        if inputs.s {
+            unit.y_velocity = unit.y_velocity.min(0.0);
+        }
+        if inputs.d {
+            unit.x_velocity += agent.speed;
+        }
+        if inputs.w && unit.grounded {
+            unit.y_velocity = unit.y_velocity.max(agent.jump);
+        }
+        //spread out from the other units, but don't wander too far from the middle of the swarm
+        for (j, other) in others.iter().enumerate() {
+            let dx = unit.x - other.x;
+            if i != j && dx.abs() < 40.0 {
+                unit.x_velocity += if dx == 0.0 {if i < j {-0.3} else {0.3}} else {dx.signum()*0.3};
+            }
+        }
+        if (centre - unit.x).abs() > 120.0 {
+            unit.x_velocity += (centre - unit.x).signum()*0.5;
+        }
+        //apply air resistance
+        unit.x_velocity *= 0.9;
+        unit.y_velocity *= 0.9;
+        //apply gravity
+        unit.y_velocity -= 1.0;
+        unit.grounded = false;
+        //check for platform collisions
+        for platform in platforms.iter() {
+            if (unit.x - platform.x).abs() < platform.width/2.0 + unit.width/2.0 && (unit.y - platform.y).abs() < platform.height/2.0 + unit.height/2.0 {
+                //check which side of the platform is closest
+                let left_overlap = (unit.x + unit.width/2.0) - (platform.x - platform.width/2.0);
+                let right_overlap = (platform.x + platform.width/2.0) - (unit.x - unit.width/2.0);
+                let bottom_overlap = (unit.y + unit.height/2.0) - (platform.y - platform.height/2.0);
+                let top_overlap = (platform.y + platform.height/2.0) - (unit.y - unit.height/2.0);
+                //find the smallest overlap
+                let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
+                //one way platforms only stop things landing on them from above
+                let passes_through = platform.kind == "one way" && (smallest_overlap != top_overlap || unit.y_velocity > 0.0);
+                if passes_through {
+                    continue;
+                }
+                //apply the smallest overlap
+                if smallest_overlap == left_overlap {
+                    unit.x_velocity = unit.x_velocity.min(0.0);
+                    unit.x = platform.x - platform.width/2.0 - unit.width/2.0 + 1.0;
+                } else if smallest_overlap == right_overlap {
+                    unit.x_velocity = unit.x_velocity.max(0.0);
+                    unit.x = platform.x + platform.width/2.0 + unit.width/2.0 - 1.0;
+                } else if smallest_overlap == top_overlap {
+                    unit.y_velocity = unit.y_velocity.max(0.0);
+                    unit.y = platform.y + platform.height/2.0 + unit.height/2.0 - 1.0;
+                    unit.grounded = true;
+                    //ride along with moving platforms, and get thrown back up by bouncy ones
+                    unit.x += platform.x_velocity;
+                    if platform.kind == "bouncy" {
+                        unit.y_velocity = agent.jump*1.3;
+                    }
+                } else if smallest_overlap == bottom_overlap {
+                    unit.y_velocity = unit.y_velocity.min(0.0);
+                    unit.y = platform.y - platform.height/2.0 - unit.height/2.0 + 1.0;
+                }
+            }
+        }
+        //check for ground collisions
+        if unit.y - unit.height/2.0 < 0.0 {
+            unit.grounded = true;
+            unit.y_velocity = unit.y_velocity.max(0.0);
+            unit.y = unit.height/2.0 - 1.0;
+        }
+        //move the unit
+        unit.x += unit.x_velocity;
+        unit.y += unit.y_velocity;
     }
-    state.player.x -= 1.0;
-    state.random_things.camera_distance += 1.0;
+    //the swarm itself sits in the middle of its units
+    agent.x = agent.units.iter().map(|unit| unit.x).sum::<f64>()/agent.units.len() as f64;
+    agent.y = agent.units.iter().map(|unit| unit.y).sum::<f64>()/agent.units.len() as f64;
+    agent.health = agent.units.iter().map(|unit| unit.health).sum();
 }
 
-fn update_enemies(mut state: &mut Game) {
-    let get_ids = state.enemies.get_ids.clone();
-    for enemy_id in (get_ids)(state) { 
-        let get = state.enemies.get.clone();
-        ((get)(enemy_id, state).update)(enemy_id, state);
+fn update_generator(agent: &mut Player, platforms: &[Platform]) -> f64 {
+    //the generator's movement and charging, the same for the player and its clones
+    //returns the charge let go if the generator was released this update, for the caller to spend on the enemies
+    let mut released = 0.0;
+    if agent.moves.sequence[agent.moves.step as usize].a {
+        agent.data_num[0] -= agent.speed;
     }
-    let mut remove_ids = vec![];
-    for enemy in state.enemies.enemies.clone() {
-        if enemy.x + enemy.width/2.0 < 0.0 || enemy.health <= 0.0 {
-            remove_ids.push(enemy.id);
+    if agent.moves.sequence[agent.moves.step as usize].s {
+        agent.data_num[1] = agent.data_num[1].min(0.0);
+    }
+    if agent.moves.sequence[agent.moves.step as usize].d {
+        agent.data_num[0] += agent.speed;
+    }
+    if agent.moves.sequence[agent.moves.step as usize].w
+        && agent.data_bool[2] {
+            agent.data_num[1] = agent.data_num[1].max(agent.jump);
+        }
+    if agent.moves.sequence[agent.moves.step as usize].special {
+        if !agent.data_bool[1] {
+            agent.data_bool[1] = true;
+            //anchor or release the generator
+            agent.data_bool[0] = !agent.data_bool[0];
+            if agent.data_bool[0] {
+                agent.data_num[5] = agent.speed;
+                agent.speed = 0.0;
+            } else {
+                agent.speed = agent.data_num[5];
+                released = agent.data_num[4];
+                agent.data_num[4] = 0.0;
+            }
         }
+    } else {
+        agent.data_bool[1] = false;
     }
-    for id in remove_ids {
-        let remove = state.enemies.remove.clone();
-        (remove)(id, state);
+    //build up charge while standing still, twice as fast while anchored
+    if agent.data_num[0].abs() < 0.2 && agent.data_bool[2] {
+        let rate = if agent.data_bool[0] {0.2} else {0.1};
+        agent.data_num[4] = (agent.data_num[4] + agent.data_num[3]*rate).min(100.0);
+    }
+    //apply air resistance
+    agent.data_num[0] *= 0.9;
+    agent.data_num[1] *= 0.9;
+    //apply gravity
+    agent.data_num[1] -= 1.0;
+    //apply bullet cooldown
+    if agent.data_num[2] > 0.0 {
+        agent.data_num[2] -= 0.05;
+    }
+    agent.data_bool[2] = false;
+    //check for platform collisions
+    collide_platforms(agent, platforms);
+    //check for ground collisions
+    if agent.y - agent.height/2.0 < 0.0 {
+        agent.data_bool[2] = true;
+        agent.data_num[1] = agent.data_num[1].max(0.0);
+        agent.y = agent.height/2.0 - 1.0;
+    }
+    //move the generator
+    agent.x += agent.data_num[0];
+    agent.y += agent.data_num[1];
+    //update the step
+    if agent.moves.step < agent.moves.length-1 {
+        agent.moves.step += 1;
+    }
+    released
+}
+
+fn power_clones(x: f64, y: f64, id: String, state: &mut Game) -> f64 {
+    //a generator speeds up the weapons of the clones close to it and repairs them, returns the charge used
+    let mut used = 0.0;
+    for clone in state.clones.players.iter_mut() {
+        if clone.active && clone.id != id && (clone.x - x).abs() < 300.0 && (clone.y - y).abs() < 300.0 {
+            if let Some(weapon) = clone.weapon.as_mut() {
+                weapon.cool_down -= 0.05;
+            }
+            clone.health += 0.05;
+            used += 0.05;
+        }
+    }
+    used
+}
+
+fn zap_enemies(damage: f64, credit: &str, state: &mut Game) {
+    //damage every enemy on screen
+    for enemy in state.enemies.enemies.iter_mut() {
+        if enemy.x + enemy.width/2.0 > 0.0 && enemy.x - enemy.width/2.0 < 1440.0 {
+            enemy.health -= damage;
+            enemy.last_hit_by = credit.to_string();
+            state.damage_numbers.push(DamageNumber {x: enemy.x, y: enemy.y + enemy.height/2.0, amount: damage, life: 0.8, colour: [1.0, 1.0, 1.0, 1.0]});
+        }
+    }
+}
+
+fn weapon(name: &str) -> Weapon {
+    //the weapons that can be carried, artifacts and pickups swap them by name
+    let blaster = Weapon {fire_rate: 1.0, projectile: "bullet".to_string(), count: 1, spread: 0.0, damage_multiplier: 1.0, speed: 10.0, blast_radius: 0.0, heat_per_shot: 0.0, max_heat: 0.0, cooling: 0.0, heat: 0.0, overheated: false, cool_down: 0.0};
+    match name {
+        "cannon" => Weapon {fire_rate: 5.0, projectile: "shell".to_string(), speed: 12.0, blast_radius: 300.0, ..blaster},
+        "scattergun" => Weapon {fire_rate: 1.4, count: 3, spread: 0.15, damage_multiplier: 0.6, ..blaster},
+        "minigun" => Weapon {fire_rate: 0.3, damage_multiplier: 0.5, heat_per_shot: 8.0, max_heat: 100.0, cooling: 0.5, ..blaster},
+        _ => blaster,
+    }
+}
+
+fn weapon_ready(weapon: &mut Weapon, shooting: bool) -> bool {
+    //cool the weapon down, then use up a shot if it is wanted and the weapon is ready
+    if weapon.cool_down > 0.0 {
+        weapon.cool_down -= 0.05;
+    }
+    weapon.heat = (weapon.heat - weapon.cooling).max(0.0);
+    if weapon.overheated && weapon.heat <= 0.0 {
+        weapon.overheated = false;
+    }
+    if !shooting || weapon.cool_down > 0.0 || weapon.overheated {
+        return false;
+    }
+    weapon.cool_down = weapon.fire_rate;
+    weapon.heat += weapon.heat_per_shot;
+    if weapon.max_heat > 0.0 && weapon.heat >= weapon.max_heat {
+        weapon.overheated = true;
+    }
+    true
+}
+
+fn fire_weapon(weapon: &Weapon, x: f64, y: f64, aim: f64, damage: f64, credit: &str, state: &mut Game) {
+    //fire one shot of the weapon along the aim, credit is who gets paid for the kills
+    emit_particles(&emitter("muzzle flash"), x, y, aim, state);
+    let middle = (weapon.count as f64 - 1.0)/2.0;
+    for i in 0..weapon.count {
+        let direction = aim + (i as f64 - middle)*weapon.spread;
+        //find all used IDs
+        let new_id = next_id(state.player_bullets.bullets.iter().map(|bullet| bullet.id));
+        let add = state.player_bullets.add.clone();
+        if weapon.projectile == "shell" {
+            (add)(Bullet {x, y, width: 10.0, height: 10.0, speed: weapon.speed, direction, damage: damage*weapon.damage_multiplier, data_string: vec![credit.to_string()], data_num: vec![direction.cos()*weapon.speed, direction.sin()*weapon.speed, weapon.blast_radius, 2.0], update: Rc::new(update_shell), id: new_id, image: 0}, state);
+        } else {
+            (add)(Bullet {x, y, width: 10.0, height: 10.0, speed: weapon.speed, direction, damage: damage*weapon.damage_multiplier, data_string: vec![credit.to_string()], data_num: vec![], update: Rc::new(update_straight_player_bullet), id: new_id, image: 0}, state);
+        }
+    }
+}
+
+fn update_straight_player_bullet(id: u32, state: &mut Game) {
+    let get = state.player_bullets.get.clone();
+    let mut bullet = (get)(id, state);
+    move_bullet(&mut bullet);
+    let remove = state.player_bullets.remove.clone();
+    (remove)(id, state);
+    let add = state.player_bullets.add.clone();
+    let overlap = state.platforms.platforms.iter().filter(|platform| {
+        bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
+    }).collect::<Vec<&Platform>>();
+    //check for enemy collisions
+    let mut hit_enemies = vec![];
+    for enemy in state.enemies.enemies.iter_mut() {
+        if bullet.x + bullet.width/2.0 > enemy.x - enemy.width/2.0 && bullet.x - bullet.width/2.0 < enemy.x + enemy.width/2.0 && bullet.y + bullet.height/2.0 > enemy.y - enemy.height/2.0 && bullet.y - bullet.height/2.0 < enemy.y + enemy.height/2.0 {
+            enemy.health -= bullet.damage;
+            enemy.last_hit_by = bullet.data_string[0].clone();
+            hit_enemies.push(enemy.id);
+            state.damage_numbers.push(DamageNumber {x: bullet.x, y: bullet.y, amount: bullet.damage, life: 0.8, colour: [1.0, 1.0, 1.0, 1.0]});
+        }
+    }
+    if overlap.is_empty() && hit_enemies.is_empty() {
+        (add)(bullet, state);
+    } else {
+        emit_particles(&emitter("impact"), bullet.x, bullet.y, 0.0, state);
+    }
+}
+
+fn move_bullet(bullet: &mut Bullet) {
+    bullet.x += bullet.direction.cos()*bullet.speed;
+    bullet.y += bullet.direction.sin()*bullet.speed;
+}
+
+fn camera_stopped(state: &Game) -> bool {
+    //a replayed boss fight stops the camera for the recorded time whether or not the boss is beaten, a new one until the boss is beaten
+    state.random_things.camera_stop > 0.0 || (state.random_things.camera_stop < 0.0 && boss_fight(state))
+}
+
+fn update_camera(state: &mut Game) {
+    //the camera stops for boss fights
+    if camera_stopped(state) {
+        if state.random_things.camera_stop > 0.0 {
+            state.random_things.camera_stop -= 1.0;
+        } else {
+            state.random_things.fight_time += 1.0;
+        }
+        return;
+    }
+    if state.random_things.camera_stop < 0.0 {
+        //a new boss fight is over, record how long it stopped the camera for
+        state.time_loop_storage.stored_boss_fights.push(state.random_things.fight_time);
+        state.random_things.camera_stop = 0.0;
+        state.random_things.fight_time = 0.0;
+    }
+    for clone in state.clones.players.iter_mut() {
+        clone.x -= 1.0;
+        for unit in clone.units.iter_mut() {
+            unit.x -= 1.0;
+        }
+    }
+    for bullet in state.player_bullets.bullets.iter_mut() {
+        bullet.x -= 1.0;
+    }
+    for bullet in state.enemy_bullets.bullets.iter_mut() {
+        bullet.x -= 1.0;
+    }
+    //a boss still alive once the camera moves on keeps up with it
+    for enemy in state.enemies.enemies.iter_mut().filter(|enemy| !enemy.boss) {
+        enemy.x -= 1.0;
+    }
+    for pickup in state.pickups.pickups.iter_mut() {
+        pickup.x -= 1.0;
+    }
+    for particle in state.particles.iter_mut() {
+        particle.x -= 1.0;
+    }
+    for number in state.damage_numbers.iter_mut() {
+        number.x -= 1.0;
+    }
+    state.player.x -= 1.0;
+    for unit in state.player.units.iter_mut() {
+        unit.x -= 1.0;
+    }
+    state.random_things.camera_distance += 1.0;
+}
+
+fn update_enemies(state: &mut Game) {
+    let get_ids = state.enemies.get_ids.clone();
+    for enemy_id in (get_ids)(state) { 
+        let get = state.enemies.get.clone();
+        ((get)(enemy_id, state).update)(enemy_id, state);
+    }
+    //pay out after the updates, so an enemy that heals itself back up, like the tank, isn't paid out until it really dies,
+    //and then it is removed straight away so it is only paid out once
+    collect_kills(state);
+    let mut remove_ids = vec![];
+    for enemy in state.enemies.enemies.clone() {
+        if enemy.x + enemy.width/2.0 < 0.0 || enemy.health <= 0.0 {
+            remove_ids.push(enemy.id);
+        }
+    }
+    for id in remove_ids {
+        let remove = state.enemies.remove.clone();
+        (remove)(id, state);
+    }
+
+    //bring in the next boss once its milestone is reached, the camera waits until it is beaten the first time
+    if state.random_things.next_boss < state.bosses.len() && state.random_things.camera_distance >= state.bosses[state.random_things.next_boss].min_distance {
+        let mut boss = new_boss(&state.bosses[state.random_things.next_boss]);
+        let new_id = next_id(state.enemies.enemies.iter().map(|enemy| enemy.id));
+        boss.id = new_id;
+        //bosses always drop something, the same thing every loop
+        boss.drop = if let Some(drop) = state.time_loop_reading.stored_boss_drops.get(state.random_things.next_boss) {
+            drop.clone()
+        } else {
+            let drop = random_pickup(1.0);
+            state.time_loop_storage.stored_boss_drops.push(drop.clone());
+            drop
+        };
+        //the camera stops for as long as it did the first time this boss was fought, so clones replaying their inputs stay in step
+        state.random_things.camera_stop = match state.time_loop_reading.stored_boss_fights.get(state.random_things.next_boss) {
+            Some(length) => *length,
+            None => -1.0,
+        };
+        let add = state.enemies.add.clone();
+        (add)(boss, state);
+        state.random_things.next_boss += 1;
+    }
+    //no normal enemies during a boss fight, only the ones the boss calls in
+    if boss_fight(state) {
+        return;
+    }
+    //hand-authored segments bring in their own enemies
+    if state.random_things.segment.is_some() {
+        state.random_things.enemy_cool_down -= 1.0/100.0;
+        return;
     }
 
     // if past max camera, add new enemies, otherwise play them back from
@@ -456,218 +1549,24 @@ fn update_enemies(mut state: &mut Game) {
     let enemy_delay = 4000.0/(state.random_things.camera_distance+1000.0);
     if state.random_things.camera_distance > state.time_loop_reading.max_camera {
         if state.random_things.enemy_cool_down <= 0.0 {
-          let mut used_ids = vec![];
-            for enemy in state.enemies.enemies.clone() {
-            used_ids.push(enemy.id);
-            }
-            let mut new_id = 0;
-            while used_ids.contains(&new_id) {
-                new_id += 1;
-            }
+            let new_id = next_id(state.enemies.enemies.iter().map(|enemy| enemy.id));
             let add = state.enemies.add.clone();
-            let enemy = Enemy {x: 1440.0 + rand::thread_rng().gen_range(50..200) as f64, y: rand::thread_rng().gen_range(0..900) as f64, width: 150.0, height: 150.0, health: 5.0, speed: 1.0, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
-                let get = state.enemies.get.clone();
-                let mut enemy = (get)(id, state);
-                enemy.x -= enemy.speed;
-                let remove = state.enemies.remove.clone();
-                (remove)(id, state);
-                //check for player collisions
-                let mut hit_player = false;
-                if enemy.x + enemy.width/2.0 > state.player.x - state.player.width/2.0 && enemy.x - enemy.width/2.0 < state.player.x + state.player.width/2.0 && enemy.y + enemy.height/2.0 > state.player.y - state.player.height/2.0 && enemy.y - enemy.height/2.0 < state.player.y + state.player.height/2.0 {
-                    if state.player.id != "Smasher".to_string() {
-                        state.player.health -= 10.0;
-                    } else {
-                        state.player.health -= 5.0;
-                    }
-                    hit_player = true;
-                }
-                //check for clone collisions
-                for clone in state.clones.players.clone() {
-                    if enemy.x + enemy.width/2.0 > clone.x - clone.width/2.0 && enemy.x - enemy.width/2.0 < clone.x + clone.width/2.0 && enemy.y + enemy.height/2.0 > clone.y - clone.height/2.0 && enemy.y - enemy.height/2.0 < clone.y + clone.height/2.0 {
-                        hit_player = true;
-                        let get = state.clones.get.clone();
-                        let mut clone = (get)(clone.id.clone(), state);
-                        if clone.id != "Smasher".to_string() {
-                            clone.health -= 10.0;
-                        } else {
-                            clone.health -= 5.0;
-                        }
-                        let remove = state.clones.remove.clone();
-                        (remove)(clone.id.clone(), state);
-                        let add = state.clones.add.clone();
-                        (add)(clone, state);
-                    }
-                }
-                //shooting
-                let mut used_ids = vec![];
-                for bullet in state.enemy_bullets.bullets.clone() {
-                    used_ids.push(bullet.id);
-                }
-                let mut new_id = 0;
-                while used_ids.contains(&new_id) {
-                    new_id += 1;
-                }
-                let add = state.enemy_bullets.add.clone();
-                if enemy.shoot_cool_down <= 0.0 {
-                    (add)(Bullet {x: enemy.x.clone(), y: enemy.y.clone(), width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: 1.0, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
-                        let get = state.enemy_bullets.get.clone();
-                        let mut bullet = (get)(id, state);
-                        bullet.x -= bullet.speed;
-                        let remove = state.enemy_bullets.remove.clone();
-                        (remove)(id, state);
-                        let add = state.enemy_bullets.add.clone();
-                        let overlap = state.platforms.platforms.clone().into_iter().filter(|platform| {
-                            bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
-                        }).collect::<Vec<Platform>>();
-                        //check for player collisions
-                        let mut hit_player = false;
-                        if bullet.x + bullet.width/2.0 > state.player.x - state.player.width/2.0 && bullet.x - bullet.width/2.0 < state.player.x + state.player.width/2.0 && bullet.y + bullet.height/2.0 > state.player.y - state.player.height/2.0 && bullet.y - bullet.height/2.0 < state.player.y + state.player.height/2.0 {
-                            state.player.health -= bullet.damage;
-                            hit_player = true;
-                        }
-                        //check for clone collisions
-                        for clone in state.clones.players.clone() {
-                            if bullet.x + bullet.width/2.0 > clone.x - clone.width/2.0 && bullet.x - bullet.width/2.0 < clone.x + clone.width/2.0 && bullet.y + bullet.height/2.0 > clone.y - clone.height/2.0 && bullet.y - bullet.height/2.0 < clone.y + clone.height/2.0 {
-                                hit_player = true;
-                                let get = state.clones.get.clone();
-                                let mut clone = (get)(clone.id.clone(), state);
-                                clone.health -= 10.0;
-                                let remove = state.clones.remove.clone();
-                                (remove)(clone.id.clone(), state);
-                                let add = state.clones.add.clone();
-                                (add)(clone, state);
-                            }
-                        }
-                        if overlap.len() == 0 && bullet.x + bullet.width/2.0 > 0.0 && !hit_player {
-                            (add)(bullet, state);
-                        }
-                    }), id: new_id, image: 0}, state);
-                    enemy.shoot_cool_down = 1.0;
-                } else {
-                    enemy.shoot_cool_down -= 1.0/100.0;
-                }
-
-
-                if enemy.x + enemy.width/2.0 > 0.0 && enemy.health > 0.0 && !hit_player {
-                    let add = state.enemies.add.clone();
-                    (add)(enemy.clone(), state);
-                }
-            }), id: new_id, image: 0, shoot_cool_down: 0.0};
+            let mut enemy = new_enemy(state);
+            enemy.id = new_id;
             (add)(enemy.clone(), state);
             state.time_loop_storage.stored_enemies.push(enemy);
             state.random_things.enemy_cool_down = enemy_delay;
         }
     } else {
         if state.random_things.enemy_cool_down <= 0.0 {
-            let mut enemy = if state.time_loop_reading.stored_enemies.len() > 0 {
+            let mut enemy = if !state.time_loop_reading.stored_enemies.is_empty() {
                 state.time_loop_reading.stored_enemies.remove(0)
             } else {
-                let mut used_ids = vec![];
-                for enemy in state.enemies.enemies.clone() {
-                    used_ids.push(enemy.id);
-                }
-                let mut new_id = 0;
-                while used_ids.contains(&new_id) {
-                    new_id += 1;
-                }
-                let add = state.enemies.add.clone();
-                let enemy = Enemy {x: 1440.0 + rand::thread_rng().gen_range(50..200) as f64, y: rand::thread_rng().gen_range(0..900) as f64, width: 150.0, height: 150.0, health: 5.0, speed: 1.0, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
-                    let get = state.enemies.get.clone();
-                    let mut enemy = (get)(id, state);
-                    enemy.x -= enemy.speed;
-                    let remove = state.enemies.remove.clone();
-                    (remove)(id, state);
-                    //check for player collisions
-                    let mut hit_player = false;
-                    if enemy.x + enemy.width/2.0 > state.player.x - state.player.width/2.0 && enemy.x - enemy.width/2.0 < state.player.x + state.player.width/2.0 && enemy.y + enemy.height/2.0 > state.player.y - state.player.height/2.0 && enemy.y - enemy.height/2.0 < state.player.y + state.player.height/2.0 {
-                        if state.player.id != "Smasher".to_string() {
-                            state.player.health -= 10.0;
-                        } else {
-                            state.player.health -= 5.0;
-                        }
-                        hit_player = true;
-                    }
-                    //check for clone collisions
-                    for clone in state.clones.players.clone() {
-                        if enemy.x + enemy.width/2.0 > clone.x - clone.width/2.0 && enemy.x - enemy.width/2.0 < clone.x + clone.width/2.0 && enemy.y + enemy.height/2.0 > clone.y - clone.height/2.0 && enemy.y - enemy.height/2.0 < clone.y + clone.height/2.0 {
-                            hit_player = true;
-                            let get = state.clones.get.clone();
-                            let mut clone = (get)(clone.id.clone(), state);
-                            if clone.id != "Smasher".to_string() {
-                                clone.health -= 10.0;
-                            } else {
-                                clone.health -= 5.0;
-                            }
-                            let remove = state.clones.remove.clone();
-                            (remove)(clone.id.clone(), state);
-                            let add = state.clones.add.clone();
-                            (add)(clone, state);
-                        }
-                    }
-                    //shooting
-                    let mut used_ids = vec![];
-                    for bullet in state.enemy_bullets.bullets.clone() {
-                        used_ids.push(bullet.id);
-                    }
-                    let mut new_id = 0;
-                    while used_ids.contains(&new_id) {
-                        new_id += 1;
-                    }
-                    let add = state.enemy_bullets.add.clone();
-                    if enemy.shoot_cool_down <= 0.0 {
-                        (add)(Bullet {x: enemy.x.clone(), y: enemy.y.clone(), width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: 1.0, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
-                            let get = state.enemy_bullets.get.clone();
-                            let mut bullet = (get)(id, state);
-                            bullet.x -= bullet.speed;
-                            let remove = state.enemy_bullets.remove.clone();
-                            (remove)(id, state);
-                            let add = state.enemy_bullets.add.clone();
-                            let overlap = state.platforms.platforms.clone().into_iter().filter(|platform| {
-                                bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
-                            }).collect::<Vec<Platform>>();
-                            //check for player collisions
-                            let mut hit_player = false;
-                            if bullet.x + bullet.width/2.0 > state.player.x - state.player.width/2.0 && bullet.x - bullet.width/2.0 < state.player.x + state.player.width/2.0 && bullet.y + bullet.height/2.0 > state.player.y - state.player.height/2.0 && bullet.y - bullet.height/2.0 < state.player.y + state.player.height/2.0 {
-                                state.player.health -= bullet.damage;
-                                hit_player = true;
-                            }
-                            //check for clone collisions
-                            for clone in state.clones.players.clone() {
-                                if bullet.x + bullet.width/2.0 > clone.x - clone.width/2.0 && bullet.x - bullet.width/2.0 < clone.x + clone.width/2.0 && bullet.y + bullet.height/2.0 > clone.y - clone.height/2.0 && bullet.y - bullet.height/2.0 < clone.y + clone.height/2.0 {
-                                    hit_player = true;
-                                    let get = state.clones.get.clone();
-                                    let mut clone = (get)(clone.id.clone(), state);
-                                    clone.health -= 10.0;
-                                    let remove = state.clones.remove.clone();
-                                    (remove)(clone.id.clone(), state);
-                                    let add = state.clones.add.clone();
-                                    (add)(clone, state);
-                                }
-                            }
-                            if overlap.len() == 0 && bullet.x + bullet.width/2.0 > 0.0 && !hit_player {
-                                (add)(bullet, state);
-                            }
-                        }), id: new_id, image: 0}, state);
-                        enemy.shoot_cool_down = 1.0;
-                    } else {
-                        enemy.shoot_cool_down -= 1.0/100.0;
-                    }
-                    if enemy.x + enemy.width/2.0 > 0.0 && enemy.health > 0.0 && !hit_player {
-                        let add = state.enemies.add.clone();
-                        (add)(enemy.clone(), state);
-                    }
-                }), id: new_id, image: 0, shoot_cool_down: 0.0};
+                let enemy = new_enemy(state);
                 state.time_loop_storage.stored_enemies.push(enemy.clone());
                 enemy
             };
-            let mut used_ids = vec![];
-            for enemy in state.enemies.enemies.clone() {
-                used_ids.push(enemy.id);
-            }
-            let mut new_id = 0;
-            while used_ids.contains(&new_id) {
-                new_id += 1;
-            }
+            let new_id = next_id(state.enemies.enemies.iter().map(|enemy| enemy.id));
             enemy.id = new_id;
             let add = state.enemies.add.clone();
             (add)(enemy.clone(), state);
@@ -677,17 +1576,352 @@ fn update_enemies(mut state: &mut Game) {
     state.random_things.enemy_cool_down -= 1.0/100.0;
 }
 
-fn make_clone(mut agent: Player, mut state: &mut Game) {
+fn current_biome(state: &Game) -> Biome {
+    //the last biome the camera has reached, so replays are always in the same biome at the same distance
+    let mut biome = state.biomes[0].clone();
+    for other in state.biomes.iter() {
+        if other.distance <= state.random_things.camera_distance {
+            biome = other.clone();
+        }
+    }
+    biome
+}
+
+fn biome_blend(state: &Game) -> (Biome, Biome, f64) {
+    //the biome before the current one, the current one, and how far the current one has faded in, from 0 to 1
+    //the backgrounds cross fade over the first few hundred distance of a biome instead of cutting straight over
+    let biome = current_biome(state);
+    let mut previous = biome.clone();
+    for other in state.biomes.iter() {
+        if other.distance < biome.distance {
+            previous = other.clone();
+        }
+    }
+    let blend = ((state.random_things.camera_distance - biome.distance)/300.0).clamp(0.0, 1.0);
+    (previous, biome, blend)
+}
+
+fn apply_biome_rule(agent: &mut Player, biome: &Biome) {
+    //push on the agent's velocity after it has moved, a swarm's velocity is in its units
+    if biome.rule == "low gravity" {
+        if agent.units.is_empty() {
+            agent.data_num[1] += biome.strength;
+        }
+        for unit in agent.units.iter_mut() {
+            unit.y_velocity += biome.strength;
+        }
+    } else if biome.rule == "wind" {
+        if agent.units.is_empty() {
+            agent.data_num[0] -= biome.strength;
+        }
+        for unit in agent.units.iter_mut() {
+            unit.x_velocity -= biome.strength;
+        }
+    }
+}
+
+fn new_enemy(state: &mut Game) -> Enemy {
+    //pick an enemy from the spawn table, only using enemies that can appear this far into the run
+    //and that live in the current biome, unless none of them do yet
+    let distance = state.random_things.camera_distance;
+    let biome = current_biome(state);
+    let mut types = state.enemy_types.iter().filter(|enemy_type| enemy_type.min_distance <= distance && biome.enemies.contains(&enemy_type.name)).cloned().collect::<Vec<EnemyType>>();
+    if types.is_empty() {
+        types = state.enemy_types.iter().filter(|enemy_type| enemy_type.min_distance <= distance).cloned().collect::<Vec<EnemyType>>();
+    }
+    let total: f64 = types.iter().map(|enemy_type| enemy_type.weight).sum();
+    let mut roll = rand::thread_rng().gen_range(0.0..total);
+    let mut enemy_type = types[types.len() - 1].clone();
+    for option in types.iter() {
+        if roll < option.weight {
+            enemy_type = option.clone();
+            break;
+        }
+        roll -= option.weight;
+    }
+    enemy_from_type(&enemy_type)
+}
+
+fn enemy_from_type(enemy_type: &EnemyType) -> Enemy {
+    Enemy {x: 1440.0 + rand::thread_rng().gen_range(50..200) as f64, y: rand::thread_rng().gen_range(0..900) as f64, width: enemy_type.width, height: enemy_type.height, health: enemy_type.health, speed: enemy_type.speed, last_hit_by: "".to_string(), data_num: enemy_type.data_num.clone(), update: enemy_type.update.clone(), id: 0, image: enemy_type.image, shoot_cool_down: 0.0, colour: enemy_type.colour, max_health: enemy_type.health, boss: false, patterns: enemy_type.patterns.clone(), drop: random_pickup(0.2)}
+}
+
+fn new_boss(boss_type: &EnemyType) -> Enemy {
+    //bosses always come in at the same height, so every loop fights them the same way
+    Enemy {x: 1440.0 + boss_type.width/2.0, y: 450.0, width: boss_type.width, height: boss_type.height, health: boss_type.health, speed: boss_type.speed, last_hit_by: "".to_string(), data_num: boss_type.data_num.clone(), update: boss_type.update.clone(), id: 0, image: boss_type.image, shoot_cool_down: 1.0, colour: boss_type.colour, max_health: boss_type.health, boss: true, patterns: boss_type.patterns.clone(), drop: "".to_string()}
+}
+
+fn boss_fight(state: &Game) -> bool {
+    state.enemies.enemies.iter().any(|enemy| enemy.boss)
+}
+
+fn boss_phase(enemy: &Enemy) -> u32 {
+    //bosses change their attacks at two thirds and one third health
+    if enemy.health > enemy.max_health*2.0/3.0 {
+        0
+    } else if enemy.health > enemy.max_health/3.0 {
+        1
+    } else {
+        2
+    }
+}
+
+fn update_shell(id: u32, state: &mut Game) {
+    //the cannoneer's shell, data_num is [x velocity, y velocity, blast radius, bounces left]
+    let get = state.player_bullets.get.clone();
+    let mut bullet = (get)(id, state);
+    let remove = state.player_bullets.remove.clone();
+    (remove)(id, state);
+    //apply gravity
+    bullet.data_num[1] -= 0.25;
+    bullet.x += bullet.data_num[0];
+    bullet.y += bullet.data_num[1];
+    //the shell goes off as soon as it touches an enemy
+    let hit_enemy = state.enemies.enemies.iter().any(|enemy| {
+        bullet.x + bullet.width/2.0 > enemy.x - enemy.width/2.0 && bullet.x - bullet.width/2.0 < enemy.x + enemy.width/2.0 && bullet.y + bullet.height/2.0 > enemy.y - enemy.height/2.0 && bullet.y - bullet.height/2.0 < enemy.y + enemy.height/2.0
+    });
+    let platform = state.platforms.platforms.iter().find(|platform| {
+        bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
+    }).cloned();
+    if hit_enemy || (platform.is_some() && bullet.data_num[3] <= 0.0) {
+        explode_shell(&bullet, state);
+        return;
+    }
+    if let Some(platform) = platform {
+        //bounce off whichever side of the platform the shell went into the least, losing some speed
+        let x_overlap = (bullet.width/2.0 + platform.width/2.0) - (bullet.x - platform.x).abs();
+        let y_overlap = (bullet.height/2.0 + platform.height/2.0) - (bullet.y - platform.y).abs();
+        if x_overlap < y_overlap {
+            bullet.x -= bullet.data_num[0];
+            bullet.data_num[0] *= -0.6;
+        } else {
+            bullet.y -= bullet.data_num[1];
+            bullet.data_num[1] *= -0.6;
+        }
+        bullet.data_num[3] -= 1.0;
+    }
+    //shells that fall off the bottom of the screen are lost
+    if bullet.y + bullet.height/2.0 > 0.0 {
+        let add = state.player_bullets.add.clone();
+        (add)(bullet, state);
+    }
+}
+
+fn explode_shell(bullet: &Bullet, state: &mut Game) {
+    //damage every enemy within the blast radius, full damage in the middle down to a quarter at the edge
+    let radius = bullet.data_num[2];
+    //the bigger the blast, the more there is of it
+    emit_particles(&Emitter {count: (radius/5.0) as u32, speed: radius/15.0, ..emitter("explosion")}, bullet.x, bullet.y, 0.0, state);
+    emit_particles(&Emitter {count: 10, ..emitter("debris")}, bullet.x, bullet.y, 0.0, state);
+    for enemy in state.enemies.enemies.iter_mut() {
+        //measure to the nearest point of the enemy, so big enemies aren't harder to hit
+        let dx = (bullet.x - enemy.x).abs() - enemy.width/2.0;
+        let dy = (bullet.y - enemy.y).abs() - enemy.height/2.0;
+        let distance = (dx.max(0.0).powi(2) + dy.max(0.0).powi(2)).sqrt();
+        if distance < radius {
+            enemy.health -= bullet.damage*(1.0 - 0.75*distance/radius);
+            enemy.last_hit_by = bullet.data_string[0].clone();
+            state.damage_numbers.push(DamageNumber {x: enemy.x, y: enemy.y + enemy.height/2.0, amount: bullet.damage*(1.0 - 0.75*distance/radius), life: 0.8, colour: [1.0, 1.0, 1.0, 1.0]});
+        }
+    }
+}
+
+fn pattern(kind: &str, count: u32, spread: f64, speed: f64, damage: f64, cool_down: f64) -> BulletPattern {
+    //a pattern aimed left with small bullets, the rest can be changed with struct update syntax
+    BulletPattern {kind: kind.to_string(), count, spread, direction: std::f64::consts::PI, speed, damage, size: 10.0, cool_down, turn: 0.0, delay: 0.0, angle: 0.0}
+}
+
+fn fire_pattern(enemy: &mut Enemy, index: usize, state: &mut Game) -> bool {
+    //fire a volley of the enemy's pattern once the cooldown has run out, returns if it fired
+    if enemy.shoot_cool_down > 0.0 {
+        enemy.shoot_cool_down -= 1.0/100.0;
+        return false;
+    }
+    let pattern = enemy.patterns[index].clone();
+    let middle = (pattern.count as f64 - 1.0)/2.0;
+    if pattern.kind == "spread" || pattern.kind == "homing" {
+        //a fan of bullets centred on the pattern's direction
+        for i in 0..pattern.count {
+            //homing bullets go after whoever is nearest when they are fired
+            let target = if pattern.kind == "homing" {nearest_agent(enemy.x, enemy.y, state).0} else {"".to_string()};
+            fire_enemy_bullet(enemy.x, enemy.y, pattern.direction + (i as f64 - middle)*pattern.spread, pattern.speed, &target, &pattern, state);
+        }
+    } else if pattern.kind == "spiral" {
+        //a ring of bullets that turns a little every volley
+        for i in 0..pattern.count {
+            fire_enemy_bullet(enemy.x, enemy.y, pattern.angle + i as f64*std::f64::consts::PI*2.0/pattern.count as f64, pattern.speed, "", &pattern, state);
+        }
+        enemy.patterns[index].angle += pattern.turn;
+    } else if pattern.kind == "aimed" {
+        //a burst at the player or the nearest clone, each bullet a bit slower so they arrive one after another
+        let (target, x, y) = nearest_agent(enemy.x, enemy.y, state);
+        let direction = (y - enemy.y).atan2(x - enemy.x);
+        for i in 0..pattern.count {
+            fire_enemy_bullet(enemy.x, enemy.y, direction + (i as f64 - middle)*pattern.spread, pattern.speed*(1.0 - 0.12*i as f64), &target, &pattern, state);
+        }
+    } else if pattern.kind == "rain" {
+        //bullets falling from the top of the screen, spaced out and swaying with each volley
+        for i in 0..pattern.count {
+            let x = 1440.0*(i as f64 + 0.5)/pattern.count as f64 + 100.0*pattern.angle.sin();
+            fire_enemy_bullet(x, 900.0, -std::f64::consts::FRAC_PI_2, pattern.speed, "", &pattern, state);
+        }
+        enemy.patterns[index].angle += pattern.turn;
+    }
+    enemy.shoot_cool_down = pattern.cool_down;
+    true
+}
+
+fn nearest_agent(x: f64, y: f64, state: &Game) -> (String, f64, f64) {
+    //who is closest to a point out of the player and the active clones, "player" for the player and the clone's id otherwise, and where they are
+    let mut nearest = ("player".to_string(), state.player.x, state.player.y);
+    let mut distance = (state.player.x - x).hypot(state.player.y - y);
+    for clone in state.clones.players.iter().filter(|clone| clone.active) {
+        if (clone.x - x).hypot(clone.y - y) < distance {
+            distance = (clone.x - x).hypot(clone.y - y);
+            nearest = (clone.id.clone(), clone.x, clone.y);
+        }
+    }
+    nearest
+}
+
+fn agent_position(target: &str, state: &Game) -> Option<(f64, f64)> {
+    //where the agent a bullet was fired at is, if it is still around
+    if target == "player" {
+        return Some((state.player.x, state.player.y));
+    }
+    state.clones.players.iter().find(|clone| clone.active && clone.id == target).map(|clone| (clone.x, clone.y))
+}
+
+fn agent_mut<'a>(target: &str, state: &'a mut Game) -> Option<&'a mut Player> {
+    //the player or the clone with that id, to be changed in place
+    if target == "player" {
+        return Some(&mut state.player);
+    }
+    state.clones.players.iter_mut().find(|clone| clone.id == target)
+}
+
+fn fire_enemy_bullet(x: f64, y: f64, direction: f64, speed: f64, target: &str, pattern: &BulletPattern, state: &mut Game) {
+    //data_string is who the bullet was fired at, empty if it wasn't fired at anyone
+    let new_id = next_id(state.enemy_bullets.bullets.iter().map(|bullet| bullet.id));
+    let add = state.enemy_bullets.add.clone();
+    if pattern.kind == "homing" {
+        //homing bullets keep how long until they home, how fast they turn and how long they home for
+        (add)(Bullet {x, y, width: pattern.size, height: pattern.size, speed, direction, damage: pattern.damage, data_string: vec![target.to_string()], data_num: vec![pattern.delay, pattern.turn, 2.0], update: Rc::new(update_homing_enemy_bullet), id: new_id, image: 0}, state);
+    } else {
+        (add)(Bullet {x, y, width: pattern.size, height: pattern.size, speed, direction, damage: pattern.damage, data_string: vec![target.to_string()], data_num: vec![], update: Rc::new(update_straight_enemy_bullet), id: new_id, image: 0}, state);
+    }
+}
+
+fn update_homing_enemy_bullet(id: u32, state: &mut Game) {
+    let get = state.enemy_bullets.get.clone();
+    let mut bullet = (get)(id, state);
+    if bullet.data_num[0] > 0.0 {
+        bullet.data_num[0] -= 1.0/100.0;
+    } else if bullet.data_num[2] > 0.0 {
+        //turn towards whoever it was fired at, but only so fast, so the bullet can be dodged, and fly straight on if they are gone
+        if let Some((x, y)) = agent_position(&bullet.data_string[0], state) {
+            let target = (y - bullet.y).atan2(x - bullet.x);
+            let difference = (target - bullet.direction + std::f64::consts::PI).rem_euclid(std::f64::consts::PI*2.0) - std::f64::consts::PI;
+            bullet.direction += difference.max(-bullet.data_num[1]).min(bullet.data_num[1]);
+        }
+        bullet.data_num[2] -= 1.0/100.0;
+    }
+    move_bullet(&mut bullet);
+    finish_enemy_bullet(bullet, state);
+}
+
+fn give_boss_rewards(menu: &mut Menu, state: &mut Game) {
+    //every boss beaten this run gives a free artifact from the pool
+    //these are picked on their own, so the choices on the artifact screen are left alone
+    for _ in 0..state.boss_rewards {
+        for artifact in roll_artifacts(menu, 1) {
+            add_artifact(artifact, menu);
+        }
+    }
+    state.boss_rewards = 0;
+}
+
+fn enemy_hits_agents(enemy: &Enemy, state: &mut Game) -> bool {
+    //damage the player and clones touching the enemy, the smasher is built for it and takes less
+    let damage = if state.player.id != "Smasher" {10.0} else {5.0};
+    let mut hit_player = hit_agent(&mut state.player, enemy.x, enemy.y, enemy.width, enemy.height, damage, &mut state.damage_numbers);
+    for clone in state.clones.players.clone() {
+        if clone.active {
+            let get = state.clones.get.clone();
+            let mut clone = (get)(clone.id.clone(), state);
+            let damage = if clone.id != "Smasher" {10.0} else {5.0};
+            if hit_agent(&mut clone, enemy.x, enemy.y, enemy.width, enemy.height, damage, &mut state.damage_numbers) {
+                hit_player = true;
+                let add = state.clones.add.clone();
+                (add)(clone, state);
+            }
+        }
+    }
+    hit_player
+}
+
+fn keep_enemy(enemy: Enemy, hit_player: bool, state: &mut Game) {
+    //put the enemy back unless it left the screen, died or crashed into the player
+    if enemy.x + enemy.width/2.0 > 0.0 && enemy.health > 0.0 && !hit_player {
+        let add = state.enemies.add.clone();
+        (add)(enemy, state);
+    }
+}
+
+fn update_straight_enemy_bullet(id: u32, state: &mut Game) {
+    let get = state.enemy_bullets.get.clone();
+    let mut bullet = (get)(id, state);
+    move_bullet(&mut bullet);
+    finish_enemy_bullet(bullet, state);
+}
+
+fn finish_enemy_bullet(bullet: Bullet, state: &mut Game) {
+    //check what the bullet hit after moving, and put it back if it didn't hit anything
+    let remove = state.enemy_bullets.remove.clone();
+    (remove)(bullet.id, state);
+    let add = state.enemy_bullets.add.clone();
+    let overlap = state.platforms.platforms.clone().into_iter().filter(|platform| {
+        bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
+    }).collect::<Vec<Platform>>();
+    //check for player collisions
+    let mut hit_player = hit_agent(&mut state.player, bullet.x, bullet.y, bullet.width, bullet.height, bullet.damage, &mut state.damage_numbers);
+    //check for clone collisions
+    for clone in state.clones.players.clone() {
+        if clone.active {
+            let get = state.clones.get.clone();
+            let mut clone = (get)(clone.id.clone(), state);
+            if hit_agent(&mut clone, bullet.x, bullet.y, bullet.width, bullet.height, bullet.damage, &mut state.damage_numbers) {
+                hit_player = true;
+                let add = state.clones.add.clone();
+                (add)(clone, state);
+            }
+        }
+    }
+    if overlap.is_empty() && bullet.x + bullet.width/2.0 > 0.0 && bullet.y + bullet.height/2.0 > 0.0 && !hit_player {
+        (add)(bullet, state);
+    } else if !overlap.is_empty() || hit_player {
+        emit_particles(&Emitter {colour: [1.0, 0.3, 0.3, 1.0], ..emitter("impact")}, bullet.x, bullet.y, 0.0, state);
+    }
+}
+
+fn make_clone(mut agent: Player, state: &mut Game) {
     let reset = agent.reset.clone();
     (reset)(&mut agent);
 
     agent.moves.step = 0;
+    agent.loop_made = state.loops;
 
     let add = state.clones.add.clone();
     (add)(agent, state);
 }
 
-fn update_platforms(mut state: &mut Game) {
+fn update_platforms(state: &mut Game) {
+    //moving and crumbling platforms keep going even while the camera is stopped
+    update_platform_kinds(state);
+    //the platforms stop with the camera while a boss is being fought
+    if camera_stopped(state) {
+        return;
+    }
     for platform in state.platforms.platforms.iter_mut() {
         platform.x -= 1.0;
     }
@@ -702,7 +1936,13 @@ fn update_platforms(mut state: &mut Game) {
 
     for id in remove_ids {
         let remove = state.platforms.remove.clone();
-        (remove)(id, &mut state);
+        (remove)(id, state);
+    }
+
+    //hand-authored segments place their own platforms
+    if state.random_things.segment.is_some() {
+        state.random_things.platform_cool_down -= 1.0/100.0;
+        return;
     }
 
     //if past max camera, add new platforms, otherwise play them back from
@@ -710,25 +1950,17 @@ fn update_platforms(mut state: &mut Game) {
 
     if state.random_things.camera_distance > state.time_loop_reading.max_camera {
         //find all used IDs
-        let mut used_ids = vec![];
-        for platform in state.platforms.platforms.clone() {
-            used_ids.push(platform.id);
-        }
-        //use a new ID
-        let mut new_id = 0;
-        while used_ids.contains(&new_id) {
-            new_id += 1;
-        }
+        let new_id = next_id(state.platforms.platforms.iter().map(|platform| platform.id));
         //add a new platform with a random image
         if state.random_things.platform_cool_down <= 0.0 {
-            let image = rand::thread_rng().gen_range(0..3);
             let add = state.platforms.add.clone();
-            let y = rand::thread_rng().gen_range(0..900) as f64;
-            let width = rand::thread_rng().gen_range(200..400) as f64;
-            let height = 50.0;
-            let x = 1440.0 + width/2.0;
-            let platform = Platform {x: x, y: y, width: width, height: height, id: new_id, image: image};
-            (add)(platform.clone(), &mut state);
+            let mut platform = new_platform(state);
+            platform.id = new_id;
+            (add)(platform.clone(), state);
+            state.random_things.path_y = platform.y;
+            if !platform.pickup.is_empty() {
+                spawn_pickup(&platform.pickup, platform.x, platform.y + platform.height/2.0 + 15.0, state);
+            }
             //add the platform to the time loop storage
             state.time_loop_storage.stored_platforms.push(platform);
             state.random_things.platform_cool_down = 1.5;
@@ -736,66 +1968,916 @@ fn update_platforms(mut state: &mut Game) {
     } else {
         if state.random_things.platform_cool_down <= 0.0 {
             //get the first platform from the time loop storage, if it exists
-            let mut platform = if state.time_loop_reading.stored_platforms.len() > 0 {
+            let mut platform = if !state.time_loop_reading.stored_platforms.is_empty() {
                 state.time_loop_reading.stored_platforms.remove(0)
             } else {
                 //if there are no platforms in the time loop storage, add a new one
-                let image = rand::thread_rng().gen_range(0..3);
-                let y = rand::thread_rng().gen_range(0..900) as f64;
-                let width = rand::thread_rng().gen_range(200..400) as f64;
-                let height = 50.0;
-                let x = 1440.0 + width/2.0;
-                let platform = Platform {x: x, y: y, width: width, height: height, id: 0, image: image};
+                let platform = new_platform(state);
                 //add the platform to the time loop storage
                 state.time_loop_storage.stored_platforms.push(platform.clone());
                 platform
             };
             //find all used IDs
-            let mut used_ids = vec![];
-            for platform in state.platforms.platforms.clone() {
-                used_ids.push(platform.id);
-            }
-            //use a new ID
-            let mut new_id = 0;
-            while used_ids.contains(&new_id) {
-                new_id += 1;
-            }
+            let new_id = next_id(state.platforms.platforms.iter().map(|platform| platform.id));
             platform.id = new_id;
             let add = state.platforms.add.clone();
-            (add)(platform, &mut state);
+            (add)(platform.clone(), state);
+            state.random_things.path_y = platform.y;
+            //the platform brings back the pickup it had when it was recorded
+            if !platform.pickup.is_empty() {
+                spawn_pickup(&platform.pickup, platform.x, platform.y + platform.height/2.0 + 15.0, state);
+            }
             state.random_things.platform_cool_down = 1.5;
         }
     }
     state.random_things.platform_cool_down -= 1.0/100.0;
 }
 
-fn update_time_loop(mut state: &mut Game) {
-    if state.time_loop_storage.max_camera == state.random_things.camera_distance {
-        state.time_loop_reading.playing = true;
-    } else {
-        state.time_loop_reading.playing = false;
+fn random_pickup(chance: f64) -> String {
+    //roll for whether there is a pickup at all, then for which kind, repairs and currency being the most common
+    if rand::thread_rng().gen_range(0.0..1.0) >= chance {
+        return "".to_string();
     }
-}
+    let kinds = [("repair", 3.0), ("currency", 3.0), ("coolant", 2.0), ("overdrive", 1.0), ("haste", 1.0)];
+    let mut roll = rand::thread_rng().gen_range(0.0..10.0);
+    for (kind, weight) in kinds.iter() {
+        if roll < *weight {
+            return kind.to_string();
+        }
+        roll -= weight;
+    }
+    "repair".to_string()
+}
+
+fn spawn_pickup(kind: &str, x: f64, y: f64, state: &mut Game) {
+    let new_id = next_id(state.pickups.pickups.iter().map(|pickup| pickup.id));
+    let add = state.pickups.add.clone();
+    (add)(Pickup {x, y, width: 25.0, height: 25.0, kind: kind.to_string(), y_velocity: 0.0, id: new_id}, state);
+}
+
+fn pickup_colour(kind: &str) -> [f32; 4] {
+    match kind {
+        "repair" => [0.0, 0.9, 0.2, 1.0],
+        "coolant" => [0.9, 0.5, 0.0, 1.0],
+        "currency" => [1.0, 0.8, 0.0, 1.0],
+        "overdrive" => [0.9, 0.0, 0.3, 1.0],
+        _ => [0.3, 0.8, 1.0, 1.0],
+    }
+}
+
+fn touches_agent(agent: &Player, x: f64, y: f64, width: f64, height: f64) -> bool {
+    //a swarm touches things with any of its units
+    if agent.units.is_empty() {
+        return x + width/2.0 > agent.x - agent.width/2.0 && x - width/2.0 < agent.x + agent.width/2.0 && y + height/2.0 > agent.y - agent.height/2.0 && y - height/2.0 < agent.y + agent.height/2.0;
+    }
+    agent.units.iter().any(|unit| x + width/2.0 > unit.x - unit.width/2.0 && x - width/2.0 < unit.x + unit.width/2.0 && y + height/2.0 > unit.y - unit.height/2.0 && y - height/2.0 < unit.y + unit.height/2.0)
+}
+
+fn add_buff(agent: &mut Player, name: &str, time: f64, start: fn(&mut Player), end: Rc<dyn Fn(&mut Player)>) {
+    //picking up a buff that is already running only makes it last longer
+    for buff in agent.buffs.iter_mut() {
+        if buff.name == name {
+            buff.time_left = time;
+            return;
+        }
+    }
+    (start)(agent);
+    agent.buffs.push(Buff {name: name.to_string(), time_left: time, end});
+}
+
+fn update_buffs(agent: &mut Player) {
+    agent.invulnerable = (agent.invulnerable - 1.0/100.0).max(0.0);
+    agent.flash = (agent.flash - 1.0/100.0).max(0.0);
+    for buff in agent.buffs.iter_mut() {
+        buff.time_left -= 1.0/100.0;
+    }
+    for buff in agent.buffs.clone() {
+        if buff.time_left <= 0.0 {
+            (buff.end)(agent);
+        }
+    }
+    agent.buffs.retain(|buff| buff.time_left > 0.0);
+}
+
+fn apply_pickup(kind: &str, agent: &mut Player) -> u32 {
+    //give the agent what the pickup holds, returning any currency it was worth
+    match kind {
+        "repair" => {
+            //only up to the health the agent started with, without taking away any it has over that
+            agent.health = (agent.health + 20.0).min(agent.max_health).max(agent.health);
+        }
+        "coolant" => {
+            //cool the weapon right down and bring back an ability charge
+            if let Some(weapon) = agent.weapon.as_mut() {
+                weapon.heat = 0.0;
+                weapon.overheated = false;
+                weapon.cool_down = 0.0;
+            }
+            if let Some(ability) = agent.ability.as_mut() {
+                ability.charges = (ability.charges + 1).min(ability.max_charges);
+            }
+        }
+        "currency" => {
+            return 10;
+        }
+        "overdrive" => {
+            add_buff(agent, "overdrive", 5.0, |agent| agent.damage *= 1.5, Rc::new(|agent: &mut Player| agent.damage /= 1.5));
+        }
+        "haste" => {
+            add_buff(agent, "haste", 5.0, |agent| agent.speed *= 1.3, Rc::new(|agent: &mut Player| agent.speed /= 1.3));
+        }
+        _ => {}
+    }
+    0
+}
+
+fn update_pickups(state: &mut Game) {
+    //pickups fall until they land on a platform or the ground, so ones carried in on a platform move along with it
+    for pickup in state.pickups.pickups.iter_mut() {
+        pickup.y_velocity -= 0.25;
+        pickup.y += pickup.y_velocity;
+        for platform in state.platforms.platforms.iter() {
+            if (pickup.x - platform.x).abs() < platform.width/2.0 + pickup.width/2.0 && (pickup.y - platform.y).abs() < platform.height/2.0 + pickup.height/2.0 && pickup.y > platform.y {
+                pickup.y = platform.y + platform.height/2.0 + pickup.height/2.0;
+                pickup.y_velocity = 0.0;
+                pickup.x += platform.x_velocity;
+            }
+        }
+        if pickup.y - pickup.height/2.0 < 0.0 {
+            pickup.y = pickup.height/2.0;
+            pickup.y_velocity = 0.0;
+        }
+    }
+
+    //the player gets first pick, then the clones
+    for pickup in state.pickups.pickups.clone() {
+        let mut collected = false;
+        if touches_agent(&state.player, pickup.x, pickup.y, pickup.width, pickup.height) {
+            state.currency += apply_pickup(&pickup.kind, &mut state.player);
+            collected = true;
+        } else {
+            let mut currency = 0;
+            for clone in state.clones.players.iter_mut().filter(|clone| clone.active) {
+                if touches_agent(clone, pickup.x, pickup.y, pickup.width, pickup.height) {
+                    currency += apply_pickup(&pickup.kind, clone);
+                    collected = true;
+                    break;
+                }
+            }
+            state.currency += currency;
+        }
+        if collected || pickup.x + pickup.width/2.0 < 0.0 {
+            let remove = state.pickups.remove.clone();
+            (remove)(pickup.id, state);
+        }
+    }
+}
+
+fn jump_reach(jump: f64, speed: f64) -> (f64, f64) {
+    //follow a jump the way the vehicles move, air resistance then gravity, to find how high it goes
+    //and how far the vehicle can get sideways before landing back at the same height
+    let mut y_velocity = jump;
+    let mut height = 0.0;
+    let mut time = 0.0;
+    loop {
+        y_velocity = y_velocity*0.9 - 1.0;
+        if y_velocity <= 0.0 {
+            break;
+        }
+        height += y_velocity;
+        time += 1.0;
+    }
+    //at full speed the vehicle moves 9 times its speed each update
+    (height, 2.0*time*9.0*speed)
+}
+
+fn layout_platform(x: f64, y: f64, width: f64, kind: &str) -> Platform {
+    //a platform for a hand-authored layout, what x and y mean is up to the layout
+    Platform {x, y, width, height: 50.0, id: 0, image: 0, pickup: "".to_string(), kind: kind.to_string(), x_velocity: 0.0, data_num: vec![]}
+}
+
+fn platform_blocker(platform: &Platform, clearance: f64, state: &Game) -> Option<f64> {
+    //leave room for a vehicle between platforms that overlap sideways, and don't put platforms on top of enemies
+    //returns the right edge of whatever is in the way, so the platform can be moved past it
+    for other in state.platforms.platforms.iter() {
+        if (platform.x - other.x).abs() < platform.width/2.0 + other.width/2.0 && (platform.y - other.y).abs() < platform.height/2.0 + other.height/2.0 + clearance {
+            return Some(other.x + other.width/2.0);
+        }
+    }
+    for enemy in state.enemies.enemies.iter() {
+        if (platform.x - enemy.x).abs() < platform.width/2.0 + enemy.width/2.0 + 30.0 && (platform.y - enemy.y).abs() < platform.height/2.0 + enemy.height/2.0 + 30.0 {
+            return Some(enemy.x + enemy.width/2.0 + 30.0);
+        }
+    }
+    None
+}
+
+fn new_platform(state: &mut Game) -> Platform {
+    //every platform is placed so the player's vehicle can jump to it from the last one
+    //the reach is worked out at the start of the run, so slowing down or being anchored doesn't shrink the gaps to nothing
+    let (reach_height, reach_distance) = state.random_things.reach;
+    let max_rise = reach_height*0.8;
+    let max_gap = (reach_distance*0.5).clamp(20.0, 150.0);
+    let platform_images = current_biome(state).platform_images;
+    let image = platform_images[rand::thread_rng().gen_range(0..platform_images.len())];
+
+    //sometimes start a hand-authored chunk, once the run has got going
+    if state.random_things.queued_platforms.is_empty() && state.random_things.camera_distance >= 500.0 && rand::thread_rng().gen_range(0.0..1.0) < 0.15 {
+        let chunk = rand::thread_rng().gen_range(0..state.platform_chunks.len());
+        state.random_things.queued_platforms = state.platform_chunks[chunk].clone();
+    }
+    if !state.random_things.queued_platforms.is_empty() {
+        let step = state.random_things.queued_platforms.remove(0);
+        //chunks are squashed to fit the vehicle, so a low jumper can still climb them
+        let rise = step.y.min(max_rise);
+        let mut platform = Platform {x: 1440.0 + step.width/2.0 + step.x.min(max_gap), y: (state.random_things.path_y + rise).clamp(25.0, 850.0), width: step.width, height: 50.0, id: 0, image, pickup: random_pickup(0.25), kind: step.kind.clone(), x_velocity: 0.0, data_num: vec![]};
+        platform_kind_data(&mut platform);
+        return platform;
+    }
+
+    //try a few spots within reach
+    let clearance = state.player.height + 20.0;
+    let mut platform = layout_platform(0.0, 0.0, 0.0, "plain");
+    for _ in 0..10 {
+        let width = rand::thread_rng().gen_range(200..400) as f64;
+        let rise = rand::thread_rng().gen_range(-250.0..max_rise);
+        let gap = rand::thread_rng().gen_range(0.0..max_gap);
+        platform = Platform {x: 1440.0 + width/2.0 + gap, y: (state.random_things.path_y + rise).clamp(25.0, 850.0), width, height: 50.0, id: 0, image, pickup: random_pickup(0.25), kind: "plain".to_string(), x_velocity: 0.0, data_num: vec![]};
+        if platform_blocker(&platform, clearance, state).is_none() {
+            break;
+        }
+    }
+    //if none of them are clear, move the last one along past whatever is in the way
+    while let Some(edge) = platform_blocker(&platform, clearance, state) {
+        platform.x = edge + platform.width/2.0 + 1.0;
+    }
+    random_platform_kind(&mut platform, state.random_things.camera_distance);
+    platform
+}
+
+fn update_segments(state: &mut Game) {
+    let camera_distance = state.random_things.camera_distance;
+    if state.random_things.segment.is_none() {
+        if !state.time_loop_reading.stored_segments.is_empty() && state.time_loop_reading.stored_segments[0].distance <= camera_distance {
+            //play back the next recorded segment once the camera reaches it
+            state.random_things.segment = Some(state.time_loop_reading.stored_segments.remove(0));
+        } else if camera_distance > state.time_loop_reading.max_camera && camera_distance >= state.random_things.next_segment {
+            //past anything recorded, splice in a random segment and record it so later loops get the same one
+            let segments = state.segments.iter().filter(|segment| segment.random).cloned().collect::<Vec<Segment>>();
+            if !segments.is_empty() {
+                let mut segment = segments[rand::thread_rng().gen_range(0..segments.len())].clone();
+                segment.distance = camera_distance;
+                roll_segment_kinds(&mut segment);
+                state.time_loop_storage.stored_segments.push(segment.clone());
+                state.time_loop_storage.stored_segments.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap());
+                state.random_things.segment = Some(segment);
+            }
+            state.random_things.next_segment = camera_distance + rand::thread_rng().gen_range(3000.0..6000.0);
+        }
+    }
+
+    let mut segment = match state.random_things.segment.take() {
+        Some(segment) => segment,
+        None => return,
+    };
+    let progress = camera_distance - segment.distance;
+    while !segment.platforms.is_empty() && segment.platforms[0].x <= progress {
+        let mut platform = segment.platforms.remove(0);
+        let new_id = next_id(state.platforms.platforms.iter().map(|platform| platform.id));
+        platform.id = new_id;
+        //things placed before the start of the segment are already on screen when it starts
+        platform.x = 1440.0 + platform.width/2.0 + platform.x - progress;
+        platform.image = current_biome(state).platform_images[0];
+        let add = state.platforms.add.clone();
+        (add)(platform.clone(), state);
+        //random platforms carry on from the segment's last one
+        state.random_things.path_y = platform.y;
+    }
+    while !segment.enemies.is_empty() && segment.enemies[0].at <= progress {
+        let spawn = segment.enemies.remove(0);
+        if let Some(enemy_type) = state.enemy_types.iter().find(|enemy_type| enemy_type.name == spawn.name) {
+            let mut enemy = enemy_from_type(enemy_type);
+            //placed exactly and carrying nothing, so the segment is the same every loop
+            enemy.x = 1440.0 + enemy.width/2.0 + spawn.at - progress;
+            enemy.y = spawn.y;
+            enemy.drop = "".to_string();
+            let new_id = next_id(state.enemies.enemies.iter().map(|enemy| enemy.id));
+            enemy.id = new_id;
+            let add = state.enemies.add.clone();
+            (add)(enemy, state);
+        }
+    }
+    while !segment.pickups.is_empty() && segment.pickups[0].at <= progress {
+        let spawn = segment.pickups.remove(0);
+        spawn_pickup(&spawn.name, 1440.0 + spawn.at - progress, spawn.y, state);
+    }
+    while !segment.triggers.is_empty() && segment.triggers[0].at <= progress {
+        let trigger = segment.triggers.remove(0);
+        (trigger.action)(state);
+    }
+    //keep the segment until it is over and everything in it has happened
+    if progress < segment.length || !segment.triggers.is_empty() {
+        state.random_things.segment = Some(segment);
+    }
+}
+
+fn editor_tools(state: &Game) -> Vec<String> {
+    //every platform kind, enemy type and pickup kind that can be placed
+    let mut tools = vec![];
+    for kind in ["plain", "moving", "crumbling", "one way", "bouncy"].iter() {
+        tools.push(format!("platform:{}", kind));
+    }
+    for enemy_type in state.enemy_types.iter() {
+        tools.push(format!("enemy:{}", enemy_type.name));
+    }
+    for kind in ["repair", "coolant", "currency", "overdrive", "haste"].iter() {
+        tools.push(format!("pickup:{}", kind));
+    }
+    tools
+}
+
+fn update_editor(menu: &mut Menu, state: &mut Game) {
+    //scroll along the segment with a and d, unless they are being typed into the name
+    if state.pressed_keys.a && !state.editor.naming {
+        state.editor.scroll = (state.editor.scroll - 10.0).max(-1440.0);
+    }
+    if state.pressed_keys.d && !state.editor.naming {
+        state.editor.scroll += 10.0;
+    }
+    menu.pressed_space = false;
+}
+
+fn editor_text(text: &str, state: &mut Game) {
+    //typing n starts a new name for the segment, this is done here rather than on the key so the n doesn't end up in the name
+    if !state.editor.naming {
+        if text == "n" {
+            state.editor.naming = true;
+            state.editor.segment.name = "".to_string();
+        }
+        return;
+    }
+    //letters, numbers, spaces, dashes and underscores go into the name, it becomes the file name so nothing else is allowed
+    for letter in text.chars() {
+        if (letter.is_ascii_alphanumeric() || letter == ' ' || letter == '-' || letter == '_') && state.editor.segment.name.len() < 24 {
+            state.editor.segment.name.push(letter.to_ascii_lowercase());
+        }
+    }
+}
+
+fn editor_click(button: MouseButton, pressed: bool, state: &mut Game) {
+    let x = state.mouse.x;
+    let y = 900.0 - state.mouse.y;
+    let scroll = state.editor.scroll;
+    if button == MouseButton::Right && pressed {
+        //remove whatever is under the mouse
+        let segment = &mut state.editor.segment;
+        segment.platforms.retain(|platform| !(x > platform.x - scroll + 1440.0 && x < platform.x - scroll + 1440.0 + platform.width && (y - platform.y).abs() < platform.height/2.0));
+        let enemy_types = state.enemy_types.clone();
+        segment.enemies.retain(|spawn| {
+            let size = enemy_types.iter().find(|enemy_type| enemy_type.name == spawn.name).map(|enemy_type| (enemy_type.width, enemy_type.height)).unwrap_or((50.0, 50.0));
+            !(x > spawn.at - scroll + 1440.0 && x < spawn.at - scroll + 1440.0 + size.0 && (y - spawn.y).abs() < size.1/2.0)
+        });
+        segment.pickups.retain(|spawn| !((x - (spawn.at - scroll + 1440.0)).abs() < 12.5 && (y - spawn.y).abs() < 12.5));
+        return;
+    }
+    if button != MouseButton::Left {
+        return;
+    }
+    let tool = editor_tools(state)[state.editor.tool].clone();
+    if let Some(kind) = tool.strip_prefix("platform:") {
+        //platforms are dragged out from one corner to the other
+        if pressed {
+            state.editor.drag = vec![x, y];
+        } else if state.editor.drag.len() == 2 {
+            let left = x.min(state.editor.drag[0]);
+            let width = (x - state.editor.drag[0]).abs().max(50.0);
+            let height = (y - state.editor.drag[1]).abs().max(20.0);
+            let middle = (y + state.editor.drag[1])/2.0;
+            let platform = Platform {height, ..layout_platform(scroll + left - 1440.0, middle, width, kind)};
+            state.editor.segment.platforms.push(platform);
+            state.editor.drag = vec![];
+        }
+    } else if pressed && tool.starts_with("enemy:") {
+        let width = state.enemy_types.iter().find(|enemy_type| enemy_type.name == tool[6..]).map(|enemy_type| enemy_type.width).unwrap_or(50.0);
+        state.editor.segment.enemies.push(SegmentSpawn {at: scroll + x - width/2.0 - 1440.0, name: tool[6..].to_string(), y});
+    } else if pressed && tool.starts_with("pickup:") {
+        state.editor.segment.pickups.push(SegmentSpawn {at: scroll + x - 1440.0, name: tool[7..].to_string(), y});
+    }
+}
+
+fn editor_key(key: Key, menu: &mut Menu, state: &mut Game) {
+    //while naming, only backspace and finishing the name do anything
+    if state.editor.naming {
+        match key {
+            Key::Backspace => {
+                state.editor.segment.name.pop();
+            }
+            Key::Return | Key::Tab => {
+                state.editor.naming = false;
+            }
+            _ => {}
+        }
+        return;
+    }
+    let tools = editor_tools(state).len();
+    match key {
+        //q and e go through the tools
+        Key::Q => {
+            state.editor.tool = (state.editor.tool + tools - 1) % tools;
+        }
+        Key::E => {
+            state.editor.tool = (state.editor.tool + 1) % tools;
+        }
+        //enter saves the segment and play-tests it with the selected vehicle
+        Key::Return => {
+            if state.editor.segment.name.trim() == "" {
+                state.editor.message = "name the segment first".to_string();
+                return;
+            }
+            save_segment(state);
+            state.editor.testing = true;
+            menu.go = true;
+            menu.keep_old = false;
+        }
+        //tab goes back to the menu
+        Key::Tab => {
+            state.editor.active = false;
+            state.editor.drag = vec![];
+        }
+        _ => {}
+    }
+}
+
+fn finish_segment(segment: &mut Segment) {
+    //put everything in the order it comes in, and end the segment once the last of it is on screen
+    segment.platforms.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+    segment.enemies.sort_by(|a, b| a.at.partial_cmp(&b.at).unwrap());
+    segment.pickups.sort_by(|a, b| a.at.partial_cmp(&b.at).unwrap());
+    let mut length: f64 = 0.0;
+    for platform in segment.platforms.iter() {
+        length = length.max(platform.x + platform.width);
+    }
+    for spawn in segment.enemies.iter().chain(segment.pickups.iter()) {
+        length = length.max(spawn.at);
+    }
+    segment.length = length;
+}
+
+fn segment_text(segment: &Segment) -> String {
+    //one line for everything in the segment, the numbers first since names can have spaces in them
+    //platform x y width height kind
+    //enemy at y type
+    //pickup at y kind
+    let mut text = "".to_string();
+    for platform in segment.platforms.iter() {
+        text += &format!("platform {} {} {} {} {}\n", platform.x, platform.y, platform.width, platform.height, platform.kind);
+    }
+    for spawn in segment.enemies.iter() {
+        text += &format!("enemy {} {} {}\n", spawn.at, spawn.y, spawn.name);
+    }
+    for spawn in segment.pickups.iter() {
+        text += &format!("pickup {} {} {}\n", spawn.at, spawn.y, spawn.name);
+    }
+    text
+}
+
+fn read_segment(name: &str, text: &str) -> Result<Segment, String> {
+    //the other way from segment_text, saying which line is wrong if one can't be read
+    let mut segment = Segment {name: name.to_string(), distance: 0.0, length: 0.0, random: true, platforms: vec![], enemies: vec![], pickups: vec![], triggers: vec![]};
+    for (i, line) in text.lines().enumerate() {
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        if words.is_empty() {
+            continue;
+        }
+        let number = |j: usize| words.get(j).and_then(|word| word.parse::<f64>().ok()).ok_or(format!("line {} of {}: expected a number", i + 1, name));
+        match words[0] {
+            "platform" if words.len() > 5 => {
+                let platform = Platform {height: number(4)?, ..layout_platform(number(1)?, number(2)?, number(3)?, &words[5..].join(" "))};
+                segment.platforms.push(platform);
+            }
+            "enemy" if words.len() > 3 => {
+                segment.enemies.push(SegmentSpawn {at: number(1)?, name: words[3..].join(" "), y: number(2)?});
+            }
+            "pickup" if words.len() > 3 => {
+                segment.pickups.push(SegmentSpawn {at: number(1)?, name: words[3..].join(" "), y: number(2)?});
+            }
+            _ => {
+                return Err(format!("line {} of {}: can't read \"{}\"", i + 1, name, line));
+            }
+        }
+    }
+    finish_segment(&mut segment);
+    Ok(segment)
+}
+
+fn asset_path(path: &str) -> std::path::PathBuf {
+    //the assets folder where the game is run from, or the nearest one above the executable if it is run from somewhere else
+    let mut places = vec![std::path::PathBuf::from(".")];
+    if let Ok(executable) = std::env::current_exe() {
+        places.extend(executable.ancestors().skip(1).map(|place| place.to_path_buf()));
+    }
+    for place in places {
+        if place.join("assets").is_dir() {
+            return place.join("assets").join(path);
+        }
+    }
+    std::path::PathBuf::from("assets").join(path)
+}
+
+fn load_segments(state: &mut Game) {
+    //read every segment saved by the editor, skipping any that can't be read and saying which in the editor
+    let entries = match std::fs::read_dir(asset_path("segments")) {
+        Ok(entries) => entries,
+        //nothing has been saved yet
+        Err(_) => return,
+    };
+    let mut skipped = vec![];
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|extension| extension.to_str()) != Some("txt") {
+            continue;
+        }
+        let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("").to_string();
+        let segment = std::fs::read_to_string(&path).map_err(|error| format!("{} couldn't be read: {}", path.display(), error)).and_then(|text| read_segment(&name, &text));
+        match segment {
+            Ok(segment) => {
+                state.segments.retain(|other| other.name != segment.name);
+                state.segments.push(segment);
+            }
+            Err(error) => skipped.push(error),
+        }
+    }
+    if !skipped.is_empty() {
+        state.editor.message = format!("segments skipped, {}", skipped.join(", "));
+    }
+}
+
+fn save_segment(state: &mut Game) {
+    finish_segment(&mut state.editor.segment);
+    //swap it into the segments the generator can use, and write it out so it is loaded next time the game starts
+    let segment = state.editor.segment.clone();
+    state.segments.retain(|other| other.name != segment.name);
+    state.segments.push(segment.clone());
+    let folder = asset_path("segments");
+    let path = folder.join(format!("{}.txt", segment.name.trim()));
+    state.editor.message = match std::fs::create_dir_all(&folder).and_then(|_| std::fs::write(&path, segment_text(&segment))) {
+        Ok(_) => format!("saved {}", segment.name.trim()),
+        Err(error) => format!("couldn't save {}: {}", segment.name.trim(), error),
+    };
+}
+
+fn random_platform_kind(platform: &mut Platform, distance: f64) {
+    //the start of a run only has plain platforms, the rest get more varied
+    if distance < 500.0 {
+        return;
+    }
+    let kinds = [("plain", 5.0), ("moving", 2.0), ("crumbling", 1.5), ("one way", 1.5), ("bouncy", 1.0)];
+    let mut roll = rand::thread_rng().gen_range(0.0..11.0);
+    for (kind, weight) in kinds.iter() {
+        if roll < *weight {
+            platform.kind = kind.to_string();
+            break;
+        }
+        roll -= weight;
+    }
+    platform_kind_data(platform);
+}
+
+fn roll_segment_kinds(segment: &mut Segment) {
+    //roll the segment's platform kinds once, when it is recorded, so every loop plays back the same paths
+    for platform in segment.platforms.iter_mut() {
+        platform_kind_data(platform);
+    }
+}
+
+fn platform_kind_data(platform: &mut Platform) {
+    if platform.kind == "moving" {
+        //data_num is [how far it moves, where it is in its path, how fast it goes through it, 1 if it moves sideways, how far it is from where it started]
+        let sideways = if rand::thread_rng().gen_range(0..2) == 0 {0.0} else {1.0};
+        platform.data_num = vec![rand::thread_rng().gen_range(100..250) as f64, 0.0, rand::thread_rng().gen_range(0.01..0.03), sideways, 0.0];
+    } else if platform.kind == "crumbling" {
+        //data_num is [how long it lasts once stood on, 1 once it has been stood on]
+        platform.data_num = vec![0.75, 0.0];
+    }
+}
+
+fn update_platform_kinds(state: &mut Game) {
+    let mut remove_ids = vec![];
+    for platform in state.platforms.platforms.iter_mut() {
+        platform.x_velocity = 0.0;
+        if platform.kind == "moving" {
+            //follow the path back and forth, it is stored with the platform so replays move the same way
+            platform.data_num[1] += platform.data_num[2];
+            let offset = platform.data_num[0]*platform.data_num[1].sin();
+            let moved = offset - platform.data_num[4];
+            platform.data_num[4] = offset;
+            if platform.data_num[3] == 1.0 {
+                platform.x += moved;
+                platform.x_velocity = moved;
+            } else {
+                platform.y += moved;
+            }
+        } else if platform.kind == "crumbling" {
+            //start crumbling once anything stands on it
+            if platform.data_num[1] == 0.0 {
+                let top = platform.y + platform.height/2.0 + 2.0;
+                if touches_agent(&state.player, platform.x, top, platform.width, 4.0) || state.clones.players.iter().any(|clone| clone.active && touches_agent(clone, platform.x, top, platform.width, 4.0)) {
+                    platform.data_num[1] = 1.0;
+                }
+            } else {
+                platform.data_num[0] -= 1.0/100.0;
+                if platform.data_num[0] <= 0.0 {
+                    remove_ids.push(platform.id);
+                }
+            }
+        }
+    }
+    for id in remove_ids {
+        let remove = state.platforms.remove.clone();
+        (remove)(id, state);
+    }
+}
+
+fn platform_colour(platform: &Platform) -> [f32; 4] {
+    //tint the platform so its kind can be told apart, crumbling ones fade as they fall apart
+    match platform.kind.as_str() {
+        "moving" => [0.7, 0.8, 1.0, 1.0],
+        "crumbling" => [0.8, 0.6, 0.4, if platform.data_num.len() > 1 && platform.data_num[1] == 1.0 {platform.data_num[0]/0.75} else {1.0} as f32],
+        "one way" => [1.0, 1.0, 1.0, 0.5],
+        "bouncy" => [0.5, 1.0, 0.5, 1.0],
+        _ => [1.0, 1.0, 1.0, 1.0],
+    }
+}
+
+fn platform_slices(sizes: [(f64, f64); 3], x: f64, y: f64, width: f64, height: f64) -> Vec<(usize, [f64; 4], [f64; 4])> {
+    //lay a tile set's left cap, middle and right cap over the box on screen so the tiles aren't stretched
+    //every tile is scaled to the box's height, the caps are squeezed if the box is too short for both and the middle is repeated and cut short at the end
+    //returns which tile, the part of it and where on the screen it goes for every piece, x and y are the top left of the box
+    let left = (sizes[0].0*height/sizes[0].1).min(width/2.0);
+    let right = (sizes[2].0*height/sizes[2].1).min(width/2.0);
+    let mut pieces = vec![(0, [0.0, 0.0, sizes[0].0, sizes[0].1], [x, y, left, height]), (2, [0.0, 0.0, sizes[2].0, sizes[2].1], [x + width - right, y, right, height])];
+    let tile = sizes[1].0*height/sizes[1].1;
+    let mut place_x = x + left;
+    while place_x < x + width - right {
+        let part = ((x + width - right - place_x)/tile).min(1.0);
+        pieces.push((1, [0.0, 0.0, sizes[1].0*part, sizes[1].1], [place_x, y, tile*part, height]));
+        place_x += tile;
+    }
+    pieces
+}
+
+fn emitter(name: &str) -> Emitter {
+    match name {
+        "explosion" => Emitter {count: 40, speed: 8.0, spread: 2.0*std::f64::consts::PI, size: 14.0, life: 0.6, colour: [1.0, 0.6, 0.1, 1.0], gravity: 0.0},
+        "muzzle flash" => Emitter {count: 5, speed: 5.0, spread: 0.6, size: 8.0, life: 0.1, colour: [1.0, 0.95, 0.6, 1.0], gravity: 0.0},
+        "debris" => Emitter {count: 20, speed: 6.0, spread: 2.0*std::f64::consts::PI, size: 10.0, life: 1.0, colour: [0.5, 0.5, 0.5, 1.0], gravity: 0.3},
+        //a bullet hitting something
+        _ => Emitter {count: 6, speed: 3.0, spread: 2.0*std::f64::consts::PI, size: 5.0, life: 0.25, colour: [1.0, 1.0, 0.8, 1.0], gravity: 0.0},
+    }
+}
+
+fn emit_particles(emitter: &Emitter, x: f64, y: f64, direction: f64, state: &mut Game) {
+    //throw the particles out at random speeds, sizes and lives up to the emitter's
+    for _ in 0..emitter.count {
+        let angle = direction + rand::thread_rng().gen_range(-0.5..0.5)*emitter.spread;
+        let speed = emitter.speed*rand::thread_rng().gen_range(0.3..1.0);
+        let life = emitter.life*rand::thread_rng().gen_range(0.5..1.0);
+        state.particles.push(Particle {x, y, x_velocity: angle.cos()*speed, y_velocity: angle.sin()*speed, size: emitter.size*rand::thread_rng().gen_range(0.4..1.0), life, max_life: life, colour: emitter.colour, gravity: emitter.gravity});
+    }
+}
+
+fn update_damage_numbers(state: &mut Game) {
+    //float up and fade out
+    for number in state.damage_numbers.iter_mut() {
+        number.y += 1.0;
+        number.life -= 1.0/100.0;
+    }
+    state.damage_numbers.retain(|number| number.life > 0.0);
+}
+
+fn letter_rows(letter: char) -> [&'static str; 7] {
+    //a 5 by 7 pixel letter or digit, one string per row from the top, upper and lower case look the same
+    //every bit of text in the game is drawn from these, through text_rectangles
+    match letter.to_ascii_lowercase() {
+        'a' => ["01110", "10001", "10001", "11111", "10001", "10001", "10001"],
+        'b' => ["11110", "10001", "10001", "11110", "10001", "10001", "11110"],
+        'c' => ["01110", "10001", "10000", "10000", "10000", "10001", "01110"],
+        'd' => ["11110", "10001", "10001", "10001", "10001", "10001", "11110"],
+        'e' => ["11111", "10000", "10000", "11110", "10000", "10000", "11111"],
+        'f' => ["11111", "10000", "10000", "11110", "10000", "10000", "10000"],
+        'g' => ["01110", "10001", "10000", "10111", "10001", "10001", "01111"],
+        'h' => ["10001", "10001", "10001", "11111", "10001", "10001", "10001"],
+        'i' => ["01110", "00100", "00100", "00100", "00100", "00100", "01110"],
+        'j' => ["00111", "00010", "00010", "00010", "00010", "10010", "01100"],
+        'k' => ["10001", "10010", "10100", "11000", "10100", "10010", "10001"],
+        'l' => ["10000", "10000", "10000", "10000", "10000", "10000", "11111"],
+        'm' => ["10001", "11011", "10101", "10101", "10001", "10001", "10001"],
+        'n' => ["10001", "10001", "11001", "10101", "10011", "10001", "10001"],
+        'o' => ["01110", "10001", "10001", "10001", "10001", "10001", "01110"],
+        'p' => ["11110", "10001", "10001", "11110", "10000", "10000", "10000"],
+        'q' => ["01110", "10001", "10001", "10001", "10101", "10010", "01101"],
+        'r' => ["11110", "10001", "10001", "11110", "10100", "10010", "10001"],
+        's' => ["01111", "10000", "10000", "01110", "00001", "00001", "11110"],
+        't' => ["11111", "00100", "00100", "00100", "00100", "00100", "00100"],
+        'u' => ["10001", "10001", "10001", "10001", "10001", "10001", "01110"],
+        'v' => ["10001", "10001", "10001", "10001", "10001", "01010", "00100"],
+        'w' => ["10001", "10001", "10001", "10101", "10101", "10101", "01010"],
+        'x' => ["10001", "10001", "01010", "00100", "01010", "10001", "10001"],
+        'y' => ["10001", "10001", "01010", "00100", "00100", "00100", "00100"],
+        'z' => ["11111", "00001", "00010", "00100", "01000", "10000", "11111"],
+        '0' => ["01110", "10001", "10011", "10101", "11001", "10001", "01110"],
+        '1' => ["00100", "01100", "00100", "00100", "00100", "00100", "01110"],
+        '2' => ["01110", "10001", "00001", "00010", "00100", "01000", "11111"],
+        '3' => ["11110", "00001", "00001", "01110", "00001", "00001", "11110"],
+        '4' => ["00010", "00110", "01010", "10010", "11111", "00010", "00010"],
+        '5' => ["11111", "10000", "11110", "00001", "00001", "10001", "01110"],
+        '6' => ["00110", "01000", "10000", "11110", "10001", "10001", "01110"],
+        '7' => ["11111", "00001", "00010", "00100", "01000", "01000", "01000"],
+        '8' => ["01110", "10001", "10001", "01110", "10001", "10001", "01110"],
+        '9' => ["01110", "10001", "10001", "01111", "00001", "00010", "01100"],
+        '.' => ["00000", "00000", "00000", "00000", "00000", "01100", "01100"],
+        ',' => ["00000", "00000", "00000", "00000", "01100", "00100", "01000"],
+        '\'' => ["00100", "00100", "01000", "00000", "00000", "00000", "00000"],
+        '-' => ["00000", "00000", "00000", "11111", "00000", "00000", "00000"],
+        '+' => ["00000", "00100", "00100", "11111", "00100", "00100", "00000"],
+        ':' => ["00000", "01100", "01100", "00000", "01100", "01100", "00000"],
+        '!' => ["00100", "00100", "00100", "00100", "00100", "00000", "00100"],
+        '?' => ["01110", "10001", "00001", "00010", "00100", "00000", "00100"],
+        '/' => ["00000", "00001", "00010", "00100", "01000", "10000", "00000"],
+        '(' => ["00010", "00100", "01000", "01000", "01000", "00100", "00010"],
+        ')' => ["01000", "00100", "00010", "00010", "00010", "00100", "01000"],
+        '_' => ["00000", "00000", "00000", "00000", "00000", "00000", "11111"],
+        _ => ["00000", "00000", "00000", "00000", "00000", "00000", "00000"],
+    }
+}
+
+fn text_rectangles(text: &str, x: f64, y: f64, size: f64) -> Vec<[f64; 4]> {
+    //the pixels of a line of text with its top middle at x, y on the screen, each pixel size wide with a pixel between letters
+    let left = x - text.chars().count() as f64*6.0*size/2.0;
+    let mut rectangles = vec![];
+    for (i, letter) in text.chars().enumerate() {
+        for (row, pixels) in letter_rows(letter).iter().enumerate() {
+            for (column, pixel) in pixels.chars().enumerate() {
+                if pixel == '1' {
+                    rectangles.push([left + (i as f64*6.0 + column as f64)*size, y + row as f64*size, size, size]);
+                }
+            }
+        }
+    }
+    rectangles
+}
+
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    //split text into lines of at most width letters, breaking between words
+    let mut lines = vec![];
+    let mut line = "".to_string();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.len() + 1 + word.len() > width {
+            lines.push(line);
+            line = "".to_string();
+        }
+        if !line.is_empty() {
+            line += " ";
+        }
+        line += word;
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn button_label(action: &str) -> String {
+    //the text drawn on buttons that don't have a text image
+    match action {
+        "drop artifact" => "drop last pick".to_string(),
+        "editor" => "editor".to_string(),
+        _ => "".to_string(),
+    }
+}
+
+fn rarity_name(rarity: u8) -> (String, [f32; 4]) {
+    match rarity {
+        0 => ("common".to_string(), [0.8, 0.8, 0.8, 1.0]),
+        1 => ("rare".to_string(), [0.3, 0.6, 1.0, 1.0]),
+        _ => ("legendary".to_string(), [1.0, 0.7, 0.1, 1.0]),
+    }
+}
+
+fn update_particles(state: &mut Game) {
+    for particle in state.particles.iter_mut() {
+        particle.y_velocity -= particle.gravity;
+        particle.x_velocity *= 0.95;
+        particle.y_velocity *= 0.95;
+        particle.x += particle.x_velocity;
+        particle.y += particle.y_velocity;
+        particle.life -= 1.0/100.0;
+    }
+    state.particles.retain(|particle| particle.life > 0.0);
+}
+
+fn frame(x_scale: f64, y_scale: f64, x_offset: f64, y_offset: f64) -> Frame {
+    Frame {rotated: false, x_scale, y_scale, x_offset, y_offset}
+}
+
+fn animation(name: &str) -> Animation {
+    match name {
+        //bounce along the ground
+        "driving" => Animation {frames: vec![frame(1.03, 0.97, 0.0, 0.0), frame(1.0, 1.0, 0.0, 1.0), frame(0.98, 1.02, 0.0, 0.0), frame(1.0, 1.0, 0.0, 1.0)], frame_time: 0.08},
+        //stretched out in the air
+        "jumping" => Animation {frames: vec![Frame {rotated: true, ..frame(0.95, 1.06, 0.0, 0.0)}], frame_time: 1.0},
+        //kicked back by each shot
+        "firing" => Animation {frames: vec![frame(0.96, 1.0, -4.0, 0.0), frame(0.98, 1.0, -2.0, 0.0), frame(1.0, 1.0, 0.0, 0.0)], frame_time: 0.05},
+        //breathing while standing still
+        _ => Animation {frames: vec![frame(1.0, 1.0, 0.0, 0.0), frame(1.0, 0.98, 0.0, 0.0), frame(1.0, 0.97, 0.0, 0.0), frame(1.0, 0.98, 0.0, 0.0)], frame_time: 0.2},
+    }
+}
+
+fn current_frame(agent: &Player) -> Frame {
+    let animation = animation(&agent.animation);
+    let i = (agent.animation_time/animation.frame_time) as usize % animation.frames.len();
+    animation.frames[i].clone()
+}
+
+fn update_animation(agent: &mut Player) {
+    //pick the animation from what the agent is doing, firing first, then in the air, then moving
+    let grounded = if agent.units.is_empty() {agent.data_bool.len() > 2 && agent.data_bool[2]} else {agent.units.iter().any(|unit| unit.grounded)};
+    let x_velocity = if agent.data_num.len() > 1 {agent.data_num[0]} else {0.0};
+    let y_velocity = if agent.data_num.len() > 1 {agent.data_num[1]} else {0.0};
+    let firing = agent.weapon.as_ref().is_some_and(|weapon| weapon.cool_down > 0.0 && weapon.cool_down > weapon.fire_rate - 0.15);
+    let name = if firing {"firing"} else if !grounded {"jumping"} else if x_velocity.abs() > 0.5 {"driving"} else {"idle"};
+    if agent.animation != name {
+        agent.animation = name.to_string();
+        agent.animation_time = 0.0;
+    } else {
+        agent.animation_time += 1.0/100.0;
+    }
+    //lean into the jump or fall, and settle flat on the ground, since platforms are flat
+    let target = if grounded {0.0} else {(y_velocity/(x_velocity.abs() + 20.0)).atan().clamp(-0.6, 0.6)};
+    agent.tilt += (target - agent.tilt)*0.2;
+}
+
+fn vehicle_transform(agent: &Player, image_size: (u32, u32), c: &Context) -> [[f64; 3]; 2] {
+    //place the image over the agent with the current frame, turned around its middle and kept resting on its bottom
+    let frame = current_frame(agent);
+    let width = agent.width*frame.x_scale;
+    let height = agent.height*frame.y_scale;
+    c.transform.trans(agent.x + frame.x_offset, 900.0 - (agent.y - agent.height/2.0 + height/2.0 + frame.y_offset)).rot_rad(-agent.tilt).trans(-width/2.0, -height/2.0).scale(width/(image_size.0 as f64), height/(image_size.1 as f64))
+}
+
+fn draw_vehicle<G: Graphics<Texture = G2dTexture>>(agent: &Player, player_images: &[G2dTexture], rot_images: &[G2dTexture], rot_index: &[Option<usize>; 6], c: &Context, g: &mut G) {
+    //draw the agent, or each of its units if it is a swarm
+    if agent.units.is_empty() {
+        let texture = match rot_index[agent.image as usize] {
+            Some(i) if current_frame(agent).rotated => &rot_images[i],
+            _ => &player_images[agent.image as usize],
+        };
+        image(texture, vehicle_transform(agent, texture.get_size(), c), g);
+    }
+    let image_size = player_images[agent.image as usize].get_size();
+    for unit in agent.units.iter() {
+        image(&player_images[agent.image as usize], c.transform.scale(unit.width/(image_size.0 as f64), unit.height/(image_size.1 as f64)).trans((unit.x - unit.width/2.0)/unit.width*(image_size.0 as f64), (900.0 - (unit.y + unit.height/2.0))/unit.height*(image_size.1 as f64)), g);
+    }
+}
+
+fn draw_flash<G: Graphics<Texture = G2dTexture>>(agent: &Player, white_images: &[G2dTexture], white_index: &[Option<usize>; 6], c: &Context, g: &mut G) {
+    //draw the white version of the agent over it, or a white box if its image has no white version
+    if agent.units.is_empty() {
+        match white_index[agent.image as usize] {
+            Some(i) => image(&white_images[i], vehicle_transform(agent, white_images[i].get_size(), c), g),
+            None => rectangle([1.0, 1.0, 1.0, 0.7], [0.0, 0.0, 1.0, 1.0], vehicle_transform(agent, (1, 1), c), g),
+        }
+        return;
+    }
+    let boxes = agent.units.iter().map(|unit| [unit.x, unit.y, unit.width, unit.height]).collect::<Vec<[f64; 4]>>();
+    for [x, y, width, height] in boxes {
+        if let Some(i) = white_index[agent.image as usize] {
+            let image_size = white_images[i].get_size();
+            image(&white_images[i], c.transform.scale(width/(image_size.0 as f64), height/(image_size.1 as f64)).trans((x - width/2.0)/width*(image_size.0 as f64), (900.0 - (y + height/2.0))/height*(image_size.1 as f64)), g);
+        } else {
+            rectangle([1.0, 1.0, 1.0, 0.7], [x - width/2.0, 900.0 - (y + height/2.0), width, height], c.transform, g);
+        }
+    }
+}
 
-fn update_menu(mut menu: &mut Menu, mut state: &mut Game) {
+fn update_time_loop(state: &mut Game) {
+    state.time_loop_reading.playing = state.time_loop_storage.max_camera == state.random_things.camera_distance;
+}
+
+fn update_menu(menu: &mut Menu, state: &mut Game) {
     //if menu.go, begin a run
     if menu.go {
         menu.go = false;
         menu.screen = 0;
         state.in_run = true;
         //set the player to the selected vehicle, if keep_old is false
-        //otherwise, keep the player the same, but apply upgrades to all vehicles in the menu
+        //otherwise, keep the player the same, the upgrades bought for its vehicle are copied over below
         if !menu.keep_old {
             state.player = menu.vehicles[menu.selected_vehicle as usize].clone();
-            //if the player is the swarm vehicle, set id to data_num[3] and increment data_num[3] for the menu car
-            if state.player.id == "Swarm".to_string() {
-                state.player.id = state.player.data_num[3].to_string();
-                menu.vehicles[menu.selected_vehicle as usize].data_num[3] += 1.0;
-            }
-        } else {
-            for vehicle in menu.vehicles.iter_mut() {
-                vehicle.health += menu.health_modifier as f64;
-                vehicle.damage += menu.damage_modifier as f64;
+            //if the player is the swarm vehicle, give it the next swarm id
+            if state.player.id == "Swarm" {
+                menu.swarms += 1;
+                state.player.id = format!("Swarm {}", menu.swarms);
             }
         }
         let mut reset = state.player.reset.clone();
@@ -804,30 +2886,68 @@ fn update_menu(mut menu: &mut Menu, mut state: &mut Game) {
             reset = clone.reset.clone();
             (reset)(clone);
             clone.moves.step = 0;
+            clone.active = true;
         }
+        //apply upgrades and artifacts after resetting, since reset sets health back to a starting value
+        state.player.upgrades = menu.vehicles[state.player.vehicle as usize].upgrades.clone();
+        apply_artifacts(&menu.artifacts, &mut state.player, &menu.vehicles);
+        for clone in state.clones.players.iter_mut() {
+            apply_clone_artifacts(&menu.artifacts, clone, &state.player, &menu.vehicles);
+        }
+        state.random_things.reach = jump_reach(state.player.jump, state.player.speed);
         let mut remove = state.player_bullets.remove.clone();
         for bullet in state.player_bullets.bullets.clone() {
-            (remove)(bullet.id, &mut state);
+            (remove)(bullet.id, state);
         }
         remove = state.enemy_bullets.remove.clone();
         for bullet in state.enemy_bullets.bullets.clone() {
-            (remove)(bullet.id, &mut state);
+            (remove)(bullet.id, state);
         }
         remove = state.enemies.remove.clone();
         for enemy in state.enemies.enemies.clone() {
-            (remove)(enemy.id, &mut state);
+            (remove)(enemy.id, state);
         }
         remove = state.platforms.remove.clone();
         for platform in state.platforms.platforms.clone() {
-            (remove)(platform.id, &mut state);
+            (remove)(platform.id, state);
         }
+        remove = state.pickups.remove.clone();
+        for pickup in state.pickups.pickups.clone() {
+            (remove)(pickup.id, state);
+        }
+        state.particles = vec![];
+        state.damage_numbers = vec![];
         state.random_things.camera_distance = 0.0;
+        state.random_things.next_boss = 0;
+        state.random_things.path_y = 100.0;
+        state.random_things.queued_platforms = vec![];
+        state.random_things.segment = None;
+        state.random_things.next_segment = 3000.0;
         state.random_things.enemy_cool_down = 0.0;
         state.random_things.platform_cool_down = 0.0;
+        state.random_things.camera_stop = 0.0;
+        state.random_things.fight_time = 0.0;
         state.time_loop_reading = state.time_loop_storage.clone();
-        //remove all clones with the same ID as the player
-        let remove = state.clones.remove.clone();
-        (remove)(state.player.id.clone(), &mut state)
+        //a play-test only has the segment being edited, with nothing recorded and no clones
+        if state.editor.testing {
+            //end the play-test once the segment is over
+            let mut segment = state.editor.segment.clone();
+            roll_segment_kinds(&mut segment);
+            segment.triggers.push(Trigger {at: segment.length, action: Rc::new(|state: &mut Game| state.player.health = 0.0)});
+            state.time_loop_reading = TimeLoopStorage {max_camera: 0.0, stored_enemies: vec![], stored_platforms: vec![], stored_segments: vec![segment], stored_boss_drops: vec![], stored_boss_fights: vec![], playing: false};
+            //the play-test records into a copy that is thrown away, so none of it reaches the real runs
+            state.editor.kept_storage = state.time_loop_storage.clone();
+            state.time_loop_storage = state.time_loop_reading.clone();
+            state.random_things.next_boss = state.bosses.len();
+            for clone in state.clones.players.iter_mut() {
+                clone.active = false;
+            }
+        }
+        //remove all clones with the same ID as the player, unless it is only a play-test
+        if !state.editor.testing {
+            let remove = state.clones.remove.clone();
+            (remove)(state.player.id.clone(), state)
+        }
     }
     //if menu.quit, quit the game
     if menu.quit {
@@ -845,6 +2965,14 @@ fn main() {
             platform_cool_down: 0.0,
             enemy_cool_down: 0.0,
             camera_distance: 0.0,
+            next_boss: 0,
+            path_y: 100.0,
+            queued_platforms: vec![],
+            segment: None,
+            next_segment: 3000.0,
+            reach: (0.0, 0.0),
+            camera_stop: 0.0,
+            fight_time: 0.0,
         },
         player: Player {
             damage: 1.0,
@@ -858,7 +2986,7 @@ fn main() {
             jump: 50.0,
             data_bool: vec![true, false, false],
             data_string: vec![],
-            data_num: vec![0.0, 0.0, 0.0],
+            data_num: vec![0.0, 0.0, 0.0, 1.0],
             moves: KeySequence {sequence: vec![], step: 0, length: 0},
             apply_inputs: Rc::new(|state: &mut Game| {
                 if state.player.moves.sequence[state.player.moves.step as usize].a {
@@ -870,11 +2998,10 @@ fn main() {
                 if state.player.moves.sequence[state.player.moves.step as usize].d {
                     state.player.data_num[0] += state.player.speed;
                 }
-                if state.player.moves.sequence[state.player.moves.step as usize].w {
-                    if state.player.data_bool[2] {
+                if state.player.moves.sequence[state.player.moves.step as usize].w
+                    && state.player.data_bool[2] {
                         state.player.data_num[1] = state.player.data_num[1].max(state.player.jump);
                     }
-                }
                 if state.player.moves.sequence[state.player.moves.step as usize].special {
                     if !state.player.data_bool[1] {
                         state.player.data_bool[1] = true;
@@ -885,80 +3012,21 @@ fn main() {
                     state.player.data_bool[1] = false;
                 }
                 //shooting
-                if state.player.data_bool[0] && state.player.data_num[2] <= 0.0 {
-                    state.player.data_num[2] = 1.0;
-                    //find all used IDs
-                    let mut used_ids = vec![];
-                    for bullet in state.player_bullets.bullets.clone() {
-                        used_ids.push(bullet.id);
+                let mut weapon = state.player.weapon.take();
+                if let Some(weapon) = weapon.as_mut() {
+                    if weapon_ready(weapon, state.player.data_bool[0]) {
+                        fire_weapon(weapon, state.player.x, state.player.y, state.player.moves.sequence[state.player.moves.step as usize].aim, state.player.damage, "player", state);
                     }
-                    //use a new ID
-                    let mut new_id = 0;
-                    while used_ids.contains(&new_id) {
-                        new_id += 1;
-                    }
-                    //add a new bullet
-                    let add = state.player_bullets.add.clone();
-                    (add)(Bullet {x: state.player.x, y: state.player.y, width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: state.player.damage, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
-                        let get = state.player_bullets.get.clone();
-                        let mut bullet = (get)(id, state);
-                        bullet.x += 10.0;
-                        let remove = state.player_bullets.remove.clone();
-                        (remove)(id, state);
-                        let add = state.player_bullets.add.clone();
-                        let overlap = state.platforms.platforms.iter().filter(|platform| {
-                            bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
-                        }).collect::<Vec<&Platform>>();
-                        //check for enemy collisions
-                        let mut hit_enemies = vec![];
-                        for mut enemy in state.enemies.enemies.iter_mut() {
-                            if bullet.x + bullet.width/2.0 > enemy.x - enemy.width/2.0 && bullet.x - bullet.width/2.0 < enemy.x + enemy.width/2.0 && bullet.y + bullet.height/2.0 > enemy.y - enemy.height/2.0 && bullet.y - bullet.height/2.0 < enemy.y + enemy.height/2.0 {
-                                enemy.health -= bullet.damage;
-                                hit_enemies.push(enemy.id);
-                            }
-                        }
-                        if overlap.len() == 0 && hit_enemies.len() == 0 {
-                            (add)(bullet, state);
-                        }
-                    }), id: new_id, image: 0}, state);
                 }
+                state.player.weapon = weapon;
                 //apply air resistance
                 state.player.data_num[0] *= 0.9;
                 state.player.data_num[1] *= 0.9;
                 //apply gravity
                 state.player.data_num[1] -= 1.0;
-                //apply bullet cooldown
-                if state.player.data_num[2] > 0.0 {
-                    state.player.data_num[2] -= 0.05;
-                }
                 state.player.data_bool[2] = false;
                 //check for platform collisions
-                for platform in state.platforms.platforms.iter() {
-                    if (state.player.x - platform.x).abs() < platform.width/2.0 + state.player.width/2.0 && (state.player.y - platform.y).abs() < platform.height/2.0 + state.player.height/2.0 {
-                        //check which side of the platform is closest
-                        let left_overlap = (state.player.x + state.player.width/2.0) - (platform.x - platform.width/2.0);
-                        let right_overlap = (platform.x + platform.width/2.0) - (state.player.x - state.player.width/2.0);
-                        let bottom_overlap = (state.player.y + state.player.height/2.0) - (platform.y - platform.height/2.0);
-                        let top_overlap = (platform.y + platform.height/2.0) - (state.player.y - state.player.height/2.0);
-                        //find the smallest overlap
-                        let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
-                        //apply the smallest overlap
-                        if smallest_overlap == left_overlap {
-                            state.player.data_num[0] = state.player.data_num[0].min(0.0);
-                            state.player.x = platform.x - platform.width/2.0 - state.player.width/2.0 + 1.0;
-                        } else if smallest_overlap == right_overlap {
-                            state.player.data_num[0] = state.player.data_num[0].max(0.0);
-                            state.player.x = platform.x + platform.width/2.0 + state.player.width/2.0 - 1.0;
-                        } else if smallest_overlap == top_overlap {
-                            state.player.data_num[1] = state.player.data_num[1].max(0.0);
-                            state.player.y = platform.y + platform.height/2.0 + state.player.height/2.0 - 1.0;
-                            state.player.data_bool[2] = true;
-                        } else if smallest_overlap == bottom_overlap {
-                            state.player.data_num[1] = state.player.data_num[1].min(0.0);
-                            state.player.y = platform.y - platform.height/2.0 - state.player.height/2.0 + 1.0;
-                        }
-                    }
-                }
+                collide_platforms(&mut state.player, &state.platforms.platforms);
                 //check for ground collisions
                 if state.player.y - state.player.height/2.0 < 0.0 {
                     state.player.data_bool[2] = true;
@@ -985,11 +3053,10 @@ fn main() {
                 if clone.moves.sequence[clone.moves.step as usize].d {
                     clone.data_num[0] += clone.speed;
                 }
-                if clone.moves.sequence[clone.moves.step as usize].w {
-                    if clone.data_bool[2] {
+                if clone.moves.sequence[clone.moves.step as usize].w
+                    && clone.data_bool[2] {
                         clone.data_num[1] = clone.data_num[1].max(clone.jump);
                     }
-                }
                 if clone.moves.sequence[clone.moves.step as usize].special {
                     if !clone.data_bool[1] {
                         clone.data_bool[1] = true;
@@ -1000,80 +3067,21 @@ fn main() {
                     clone.data_bool[1] = false;
                 }
                 //shooting
-                if clone.data_bool[0] && clone.data_num[2] <= 0.0 {
-                    clone.data_num[2] = 1.0;
-                    //find all used IDs
-                    let mut used_ids = vec![];
-                    for bullet in state.player_bullets.bullets.clone() {
-                        used_ids.push(bullet.id);
-                    }
-                    //use a new ID
-                    let mut new_id = 0;
-                    while used_ids.contains(&new_id) {
-                        new_id += 1;
+                let mut weapon = clone.weapon.take();
+                if let Some(weapon) = weapon.as_mut() {
+                    if weapon_ready(weapon, clone.data_bool[0]) {
+                        fire_weapon(weapon, clone.x, clone.y, clone.moves.sequence[clone.moves.step as usize].aim, clone.damage, "clone", state);
                     }
-                    //add a new bullet
-                    let add = state.player_bullets.add.clone();
-                    (add)(Bullet {x: clone.x, y: clone.y, width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: clone.damage, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
-                        let get = state.player_bullets.get.clone();
-                        let mut bullet = (get)(id, state);
-                        bullet.x += 10.0;
-                        let remove = state.player_bullets.remove.clone();
-                        (remove)(id, state);
-                        let add = state.player_bullets.add.clone();
-                        let overlap = state.platforms.platforms.iter().filter(|platform| {
-                            bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
-                        }).collect::<Vec<&Platform>>();
-                        //check for enemy collisions
-                        let mut hit_enemies = vec![];
-                        for mut enemy in state.enemies.enemies.iter_mut() {
-                            if bullet.x + bullet.width/2.0 > enemy.x - enemy.width/2.0 && bullet.x - bullet.width/2.0 < enemy.x + enemy.width/2.0 && bullet.y + bullet.height/2.0 > enemy.y - enemy.height/2.0 && bullet.y - bullet.height/2.0 < enemy.y + enemy.height/2.0 {
-                                enemy.health -= bullet.damage;
-                                hit_enemies.push(enemy.id);
-                            }
-                        }
-                        if overlap.len() == 0 && hit_enemies.len() == 0 {
-                            (add)(bullet, state);
-                        }
-                    }), id: new_id, image: 0}, state);
                 }
+                clone.weapon = weapon;
                 //apply air resistance
                 clone.data_num[0] *= 0.9;
                 clone.data_num[1] *= 0.9;
                 //apply gravity
                 clone.data_num[1] -= 1.0;
-                //apply bullet cooldown
-                if clone.data_num[2] > 0.0 {
-                    clone.data_num[2] -= 0.05;
-                }
                 clone.data_bool[2] = false;
                 //check for platform collisions
-                for platform in state.platforms.platforms.iter() {
-                    if (clone.x - platform.x).abs() < platform.width/2.0 + clone.width/2.0 && (clone.y - platform.y).abs() < platform.height/2.0 + clone.height/2.0 {
-                        //check which side of the platform is closest
-                        let left_overlap = (clone.x + clone.width/2.0) - (platform.x - platform.width/2.0);
-                        let right_overlap = (platform.x + platform.width/2.0) - (clone.x - clone.width/2.0);
-                        let bottom_overlap = (clone.y + clone.height/2.0) - (platform.y - platform.height/2.0);
-                        let top_overlap = (platform.y + platform.height/2.0) - (clone.y - clone.height/2.0);
-                        //find the smallest overlap
-                        let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
-                        //apply the smallest overlap
-                        if smallest_overlap == left_overlap {
-                            clone.data_num[0] = clone.data_num[0].min(0.0);
-                            clone.x = platform.x - platform.width/2.0 - clone.width/2.0 + 1.0;
-                        } else if smallest_overlap == right_overlap {
-                            clone.data_num[0] = clone.data_num[0].max(0.0);
-                            clone.x = platform.x + platform.width/2.0 + clone.width/2.0 - 1.0;
-                        } else if smallest_overlap == top_overlap {
-                            clone.data_num[1] = clone.data_num[1].max(0.0);
-                            clone.y = platform.y + platform.height/2.0 + clone.height/2.0 - 1.0;
-                            clone.data_bool[2] = true;
-                        } else if smallest_overlap == bottom_overlap {
-                            clone.data_num[1] = clone.data_num[1].min(0.0);
-                            clone.y = platform.y - platform.height/2.0 - clone.height/2.0 + 1.0;
-                        }
-                    }
-                }
+                collide_platforms(&mut clone, &state.platforms.platforms);
                 //check for ground collisions
                 if clone.y - clone.height/2.0 < 0.0 {
                     clone.data_bool[2] = true;
@@ -1096,10 +3104,25 @@ fn main() {
                 player.health = 100.0;
                 player.data_bool = vec![true, false, false];
                 player.data_string = vec![];
-                player.data_num = vec![0.0, 0.0, 0.0];
+                player.data_num = vec![0.0, 0.0, 0.0, 1.0];
             }),
             active: true,
             image: 2,
+            units: vec![],
+            buffs: vec![],
+            invulnerable: 0.0,
+            flash: 0.0,
+            animation: "idle".to_string(),
+            animation_time: 0.0,
+            tilt: 0.0,
+            max_health: 0.0,
+            revived: false,
+            ability: Some(ability("dash")),
+            weapon: Some(weapon("blaster")),
+            loop_made: 0,
+            upgrades: vec![],
+            vehicle: 0,
+            artifacts: vec![],
         },
         /*player: Player {
             damage: 1.0,
@@ -1154,7 +3177,7 @@ fn main() {
                     }
                     //add a new bullet
                     let add = state.player_bullets.add.clone();
-                    (add)(Bullet {x: state.player.x, y: state.player.y, width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: state.player.damage, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
+                    (add)(Bullet {x: state.player.x, y: state.player.y, width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: state.player.damage, data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
                         let get = state.player_bullets.get.clone();
                         let mut bullet = (get)(id, state);
                         bullet.x += 10.0;
@@ -1269,7 +3292,7 @@ fn main() {
                     }
                     //add a new bullet
                     let add = state.player_bullets.add.clone();
-                    (add)(Bullet {x: clone.x, y: clone.y, width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: clone.damage, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
+                    (add)(Bullet {x: clone.x, y: clone.y, width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: clone.damage, data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
                         let get = state.player_bullets.get.clone();
                         let mut bullet = (get)(id, state);
                         bullet.x += 10.0;
@@ -1358,7 +3381,7 @@ fn main() {
         },*/
         clones: PlayerList {
             players: vec![],
-            add: Rc::new(|agent: Player, mut state: &mut Game| {
+            add: Rc::new(|agent: Player, state: &mut Game| {
                 let mut new = true;
                 for player in state.clones.players.iter_mut() {
                     if agent.id == player.id {
@@ -1378,19 +3401,13 @@ fn main() {
                 }
                 state.clones.players[0].clone()
             }),
-            remove: Rc::new(|id: String, mut state: &mut Game| {
-                let mut i = 0;
-                for player in state.clones.players.clone() {
-                    if player.id == id {
-                        state.clones.players.remove(i);
-                    }
-                    i += 1;
-                }
+            remove: Rc::new(|id: String, state: &mut Game| {
+                state.clones.players.retain(|player| player.id != id);
             }),
         },
         player_bullets: BulletList {
             bullets: vec![],
-            add: Rc::new(|agent: Bullet, mut state: &mut Game| {
+            add: Rc::new(|agent: Bullet, state: &mut Game| {
                 let mut new = true;
                 for bullet in state.player_bullets.bullets.iter_mut() {
                     if agent.id == bullet.id {
@@ -1410,19 +3427,13 @@ fn main() {
                 }
                 state.player_bullets.bullets[0].clone()
             }),
-            remove: Rc::new(|id: u32, mut state: &mut Game| {
-                let mut i = 0;
-                for bullet in state.player_bullets.bullets.clone() {
-                    if bullet.id == id {
-                        state.player_bullets.bullets.remove(i);
-                    }
-                    i += 1;
-                }
+            remove: Rc::new(|id: u32, state: &mut Game| {
+                state.player_bullets.bullets.retain(|bullet| bullet.id != id);
             }),
         },
         enemy_bullets: BulletList {
             bullets: vec![],
-            add: Rc::new(|agent: Bullet, mut state: &mut Game| {
+            add: Rc::new(|agent: Bullet, state: &mut Game| {
                 let mut new = true;
                 for bullet in state.enemy_bullets.bullets.iter_mut() {
                     if agent.id == bullet.id {
@@ -1443,19 +3454,13 @@ fn main() {
                 }
                 state.enemy_bullets.bullets[0].clone()
             }),
-            remove: Rc::new(|id: u32, mut state: &mut Game| {
-                let mut i = 0;
-                for bullet in state.enemy_bullets.bullets.clone() {
-                    if bullet.id == id {
-                        state.enemy_bullets.bullets.remove(i);
-                    }
-                    i += 1;
-                }
+            remove: Rc::new(|id: u32, state: &mut Game| {
+                state.enemy_bullets.bullets.retain(|bullet| bullet.id != id);
             }),
         },
         enemies: EnemyList {
             enemies: vec![],
-            add: Rc::new(|agent: Enemy, mut state: &mut Game| {
+            add: Rc::new(|agent: Enemy, state: &mut Game| {
                 let mut new = true;
                 for enemy in state.enemies.enemies.iter_mut() {
                     if agent.id == enemy.id {
@@ -1476,14 +3481,8 @@ fn main() {
                 }
                 state.enemies.enemies[0].clone()
             }),
-            remove: Rc::new(|id: u32, mut state: &mut Game| {
-                let mut i = 0;
-                for enemy in state.enemies.enemies.clone() {
-                    if enemy.id == id {
-                        state.enemies.enemies.remove(i);
-                    }
-                    i += 1;
-                }
+            remove: Rc::new(|id: u32, state: &mut Game| {
+                state.enemies.enemies.retain(|enemy| enemy.id != id);
             }),
             get_ids: Rc::new(|state: &mut Game| -> Vec<u32> {
                 let mut ids = vec![];
@@ -1496,11 +3495,11 @@ fn main() {
         in_run: true,
         tutorial: 0,
         pressed_keys: Keys {
-            a: false, s: false, d: false, w:false, special: false, ability: false
+            a: false, s: false, d: false, w:false, special: false, ability: false, aim: 0.0
         },
         platforms: PlatformList {
             platforms: vec![],
-            add: Rc::new(|agent: Platform, mut state: &mut Game| {
+            add: Rc::new(|agent: Platform, state: &mut Game| {
                 let mut new = true;
                 for platform in state.platforms.platforms.iter_mut() {
                     if agent.id == platform.id {
@@ -1513,22 +3512,17 @@ fn main() {
                     state.platforms.platforms.push(agent);
                 }
             }),
-            get: Rc::new(|id: u32, state: &mut Game| -> Platform {
-                for platform in state.platforms.platforms.clone() {
-                    if platform.id == id {
-                        return platform;
-                    }
-                }
-                state.platforms.platforms[0].clone()
+            remove: Rc::new(|id: u32, state: &mut Game| {
+                state.platforms.platforms.retain(|platform| platform.id != id);
             }),
-            remove: Rc::new(|id: u32, mut state: &mut Game| {
-                let mut i = 0;
-                for platform in state.platforms.platforms.clone() {
-                    if platform.id == id {
-                        state.platforms.platforms.remove(i);
-                    }
-                    i += 1;
-                }
+        },
+        pickups: PickupList {
+            pickups: vec![],
+            add: Rc::new(|pickup: Pickup, state: &mut Game| {
+                state.pickups.pickups.push(pickup);
+            }),
+            remove: Rc::new(|id: u32, state: &mut Game| {
+                state.pickups.pickups.retain(|pickup| pickup.id != id);
             }),
         },
         mouse: Mouse {
@@ -1539,19 +3533,338 @@ fn main() {
             max_camera: 0.0,
             stored_enemies: vec![],
             stored_platforms: vec![],
-            end_enemy_cool_down: 0.0,
-            end_platform_cool_down: 0.0,
+            stored_segments: vec![],
+            stored_boss_drops: vec![],
+            stored_boss_fights: vec![],
             playing: false,
         },
         time_loop_reading: TimeLoopStorage {
             max_camera: 0.0,
             stored_enemies: vec![],
             stored_platforms: vec![],
-            end_enemy_cool_down: 0.0,
-            end_platform_cool_down: 0.0,
+            stored_segments: vec![],
+            stored_boss_drops: vec![],
+            stored_boss_fights: vec![],
             playing: false,
         },
+        loops: 0,
+        currency: 0,
+        bosses: vec![
+            EnemyType {
+                name: "warden".to_string(),
+                width: 350.0,
+                height: 350.0,
+                health: 150.0,
+                speed: 2.0,
+                image: 1,
+                colour: [1.0, 0.3, 0.3, 1.0],
+                weight: 0.0,
+                min_distance: 3000.0,
+                data_num: vec![0.0, 0.0],
+                patterns: vec![
+                    pattern("spread", 3, 0.08, 8.0, 5.0, 1.0),
+                    pattern("aimed", 5, 0.0, 10.0, 5.0, 0.9),
+                    pattern("spread", 7, 0.2, 9.0, 5.0, 0.6),
+                ],
+                update: Rc::new(|id: u32, state: &mut Game| {
+                    let get = state.enemies.get.clone();
+                    let mut enemy = (get)(id, state);
+                    let remove = state.enemies.remove.clone();
+                    (remove)(id, state);
+                    //slide in, then drift up and down in front of the player
+                    if enemy.x > 1150.0 {
+                        enemy.x -= enemy.speed;
+                    }
+                    enemy.data_num[0] += 0.01;
+                    enemy.y = 450.0 + 250.0*enemy.data_num[0].sin();
+                    enemy_hits_agents(&enemy, state);
+                    //a tight fan, then bursts at the player, then a wide fan with a drifter called in every few volleys
+                    let phase = boss_phase(&enemy);
+                    if fire_pattern(&mut enemy, phase as usize, state) && phase == 2 {
+                        enemy.data_num[1] += 1.0;
+                        if enemy.data_num[1] % 4.0 == 0.0 {
+                            let mut minion = enemy_from_type(&state.enemy_types[0]);
+                            minion.y = enemy.y;
+                            let new_id = next_id(state.enemies.enemies.iter().map(|other| other.id).chain([enemy.id]));
+                            minion.id = new_id;
+                            let add = state.enemies.add.clone();
+                            (add)(minion, state);
+                        }
+                    }
+                    //bosses don't die from crashing into the player
+                    keep_enemy(enemy, false, state);
+                }),
+            },
+            EnemyType {
+                name: "colossus".to_string(),
+                width: 500.0,
+                height: 300.0,
+                health: 400.0,
+                speed: 1.0,
+                image: 1,
+                colour: [0.6, 0.3, 1.0, 1.0],
+                weight: 0.0,
+                min_distance: 10000.0,
+                data_num: vec![],
+                patterns: vec![
+                    BulletPattern {turn: 0.5, size: 20.0, ..pattern("rain", 4, 0.0, 6.0, 8.0, 1.2)},
+                    BulletPattern {turn: 0.3, ..pattern("spiral", 8, 0.0, 6.0, 5.0, 0.5)},
+                    BulletPattern {direction: 0.0, delay: 0.5, turn: 0.04, ..pattern("homing", 6, std::f64::consts::PI/3.0, 7.0, 5.0, 0.7)},
+                ],
+                update: Rc::new(|id: u32, state: &mut Game| {
+                    let get = state.enemies.get.clone();
+                    let mut enemy = (get)(id, state);
+                    let remove = state.enemies.remove.clone();
+                    (remove)(id, state);
+                    //slide in and sit in the top right corner
+                    if enemy.x > 1100.0 {
+                        enemy.x -= enemy.speed;
+                    }
+                    enemy.y = 700.0;
+                    enemy_hits_agents(&enemy, state);
+                    //bombs across the screen, then a spinning ring, then homing shots in every direction
+                    let phase = boss_phase(&enemy);
+                    fire_pattern(&mut enemy, phase as usize, state);
+                    keep_enemy(enemy, false, state);
+                }),
+            },
+        ],
+        boss_rewards: 0,
+        platform_chunks: vec![
+            //a staircase
+            vec![layout_platform(0.0, 100.0, 250.0, "plain"), layout_platform(0.0, 100.0, 250.0, "plain"), layout_platform(0.0, 100.0, 250.0, "plain")],
+            //a bouncy pad down low that throws the player up to a high ledge
+            vec![layout_platform(50.0, -300.0, 300.0, "bouncy"), layout_platform(100.0, 400.0, 250.0, "plain")],
+            //a stack of one way platforms to jump up through
+            vec![layout_platform(0.0, 150.0, 350.0, "one way"), layout_platform(0.0, 150.0, 350.0, "one way"), layout_platform(0.0, 150.0, 300.0, "plain")],
+            //crumbling stepping stones that have to be crossed quickly
+            vec![layout_platform(80.0, 0.0, 200.0, "crumbling"), layout_platform(80.0, 50.0, 200.0, "crumbling"), layout_platform(80.0, 50.0, 300.0, "plain")],
+        ],
+        enemy_types: vec![
+            EnemyType {
+                name: "drifter".to_string(),
+                width: 150.0,
+                height: 150.0,
+                health: 5.0,
+                speed: 1.0,
+                image: 0,
+                colour: [1.0, 1.0, 1.0, 1.0],
+                weight: 1.0,
+                min_distance: 0.0,
+                data_num: vec![],
+                patterns: vec![pattern("spread", 1, 0.0, 10.0, 1.0, 1.0)],
+                update: Rc::new(|id: u32, state: &mut Game| {
+                    let get = state.enemies.get.clone();
+                    let mut enemy = (get)(id, state);
+                    let remove = state.enemies.remove.clone();
+                    (remove)(id, state);
+                    //drift left, shooting straight ahead
+                    enemy.x -= enemy.speed;
+                    let hit_player = enemy_hits_agents(&enemy, state);
+                    fire_pattern(&mut enemy, 0, state);
+                    keep_enemy(enemy, hit_player, state);
+                }),
+            },
+            EnemyType {
+                name: "turret".to_string(),
+                width: 100.0,
+                height: 100.0,
+                health: 8.0,
+                speed: 0.0,
+                image: 1,
+                colour: [1.0, 0.6, 0.6, 1.0],
+                weight: 0.6,
+                min_distance: 1500.0,
+                data_num: vec![],
+                patterns: vec![pattern("aimed", 3, 0.0, 9.0, 1.0, 1.2)],
+                update: Rc::new(|id: u32, state: &mut Game| {
+                    let get = state.enemies.get.clone();
+                    let mut enemy = (get)(id, state);
+                    let remove = state.enemies.remove.clone();
+                    (remove)(id, state);
+                    //stays where it is and fires bursts at the player
+                    let hit_player = enemy_hits_agents(&enemy, state);
+                    fire_pattern(&mut enemy, 0, state);
+                    keep_enemy(enemy, hit_player, state);
+                }),
+            },
+            EnemyType {
+                name: "diver".to_string(),
+                width: 80.0,
+                height: 80.0,
+                health: 3.0,
+                speed: 4.0,
+                image: 0,
+                colour: [1.0, 0.5, 0.2, 1.0],
+                weight: 0.8,
+                min_distance: 2500.0,
+                data_num: vec![],
+                patterns: vec![],
+                update: Rc::new(|id: u32, state: &mut Game| {
+                    let get = state.enemies.get.clone();
+                    let mut enemy = (get)(id, state);
+                    let remove = state.enemies.remove.clone();
+                    (remove)(id, state);
+                    //drift in slowly, then dive at the player once close enough, it doesn't shoot
+                    if enemy.x - state.player.x < 500.0 {
+                        let dx = state.player.x - enemy.x;
+                        let dy = state.player.y - enemy.y;
+                        let length = (dx*dx + dy*dy).sqrt().max(1.0);
+                        enemy.x += dx/length*enemy.speed;
+                        enemy.y += dy/length*enemy.speed;
+                    } else {
+                        enemy.x -= 1.0;
+                    }
+                    let hit_player = enemy_hits_agents(&enemy, state);
+                    keep_enemy(enemy, hit_player, state);
+                }),
+            },
+            EnemyType {
+                name: "flyer".to_string(),
+                width: 100.0,
+                height: 60.0,
+                health: 4.0,
+                speed: 1.5,
+                image: 0,
+                colour: [0.5, 0.8, 1.0, 1.0],
+                weight: 0.8,
+                min_distance: 4000.0,
+                data_num: vec![0.0, 0.0],
+                patterns: vec![pattern("spread", 3, 0.3, 8.0, 1.0, 1.2)],
+                update: Rc::new(|id: u32, state: &mut Game| {
+                    let get = state.enemies.get.clone();
+                    let mut enemy = (get)(id, state);
+                    let remove = state.enemies.remove.clone();
+                    (remove)(id, state);
+                    //weave up and down along a sine wave around the height it spawned at
+                    if enemy.data_num[1] == 0.0 {
+                        enemy.data_num[0] = enemy.y.clamp(200.0, 700.0);
+                    }
+                    enemy.data_num[1] += 0.03;
+                    enemy.x -= enemy.speed;
+                    enemy.y = enemy.data_num[0] + 150.0*enemy.data_num[1].sin();
+                    let hit_player = enemy_hits_agents(&enemy, state);
+                    fire_pattern(&mut enemy, 0, state);
+                    keep_enemy(enemy, hit_player, state);
+                }),
+            },
+            EnemyType {
+                name: "tank".to_string(),
+                width: 200.0,
+                height: 150.0,
+                health: 20.0,
+                speed: 0.5,
+                image: 1,
+                colour: [0.6, 1.0, 0.6, 1.0],
+                weight: 0.5,
+                min_distance: 6000.0,
+                data_num: vec![20.0],
+                patterns: vec![BulletPattern {delay: 0.4, turn: 0.03, ..pattern("homing", 2, 0.4, 6.0, 2.0, 2.0)}],
+                update: Rc::new(|id: u32, state: &mut Game| {
+                    let get = state.enemies.get.clone();
+                    let mut enemy = (get)(id, state);
+                    let remove = state.enemies.remove.clone();
+                    (remove)(id, state);
+                    //the shield takes most of the damage, except just after the tank fires
+                    let damage = enemy.data_num[0] - enemy.health;
+                    if damage > 0.0 && enemy.shoot_cool_down < 1.5 {
+                        enemy.health += damage*0.75;
+                    }
+                    enemy.x -= enemy.speed;
+                    let hit_player = enemy_hits_agents(&enemy, state);
+                    fire_pattern(&mut enemy, 0, state);
+                    enemy.data_num[0] = enemy.health;
+                    keep_enemy(enemy, hit_player, state);
+                }),
+            },
+            EnemyType {
+                name: "bomber".to_string(),
+                width: 160.0,
+                height: 80.0,
+                health: 6.0,
+                speed: 2.0,
+                image: 0,
+                colour: [0.8, 0.8, 0.3, 1.0],
+                weight: 0.5,
+                min_distance: 8000.0,
+                data_num: vec![],
+                patterns: vec![BulletPattern {direction: -std::f64::consts::FRAC_PI_2, size: 20.0, ..pattern("spread", 1, 0.0, 6.0, 5.0, 0.8)}],
+                update: Rc::new(|id: u32, state: &mut Game| {
+                    let get = state.enemies.get.clone();
+                    let mut enemy = (get)(id, state);
+                    let remove = state.enemies.remove.clone();
+                    (remove)(id, state);
+                    //flies along the top of the screen dropping bombs
+                    enemy.y = 800.0;
+                    enemy.x -= enemy.speed;
+                    let hit_player = enemy_hits_agents(&enemy, state);
+                    fire_pattern(&mut enemy, 0, state);
+                    keep_enemy(enemy, hit_player, state);
+                }),
+            },
+        ],
+        segments: vec![
+            //the opening, an easy climb with a couple of drifters to shoot and something to pick up
+            Segment {
+                name: "opening".to_string(),
+                distance: 0.0,
+                length: 1200.0,
+                random: false,
+                platforms: vec![layout_platform(0.0, 100.0, 400.0, "plain"), layout_platform(150.0, 250.0, 350.0, "plain"), layout_platform(300.0, 100.0, 300.0, "plain"), layout_platform(450.0, 250.0, 350.0, "one way"), layout_platform(600.0, 100.0, 300.0, "bouncy"), layout_platform(750.0, 450.0, 300.0, "plain"), layout_platform(900.0, 300.0, 350.0, "plain"), layout_platform(1050.0, 150.0, 400.0, "plain")],
+                enemies: vec![SegmentSpawn {at: 400.0, name: "drifter".to_string(), y: 350.0}, SegmentSpawn {at: 800.0, name: "drifter".to_string(), y: 550.0}],
+                pickups: vec![SegmentSpawn {at: 300.0, name: "repair".to_string(), y: 150.0}, SegmentSpawn {at: 900.0, name: "currency".to_string(), y: 350.0}],
+                triggers: vec![
+                    Trigger {at: 0.0, action: Rc::new(|state: &mut Game| state.tutorial = 1)},
+                    Trigger {at: 1200.0, action: Rc::new(|state: &mut Game| state.tutorial = 2)},
+                ],
+            },
+            //a crossing of crumbling platforms covered by turrets
+            Segment {
+                name: "gauntlet".to_string(),
+                distance: 0.0,
+                length: 900.0,
+                random: true,
+                platforms: vec![layout_platform(0.0, 200.0, 300.0, "plain"), layout_platform(150.0, 250.0, 200.0, "crumbling"), layout_platform(300.0, 300.0, 200.0, "crumbling"), layout_platform(450.0, 250.0, 200.0, "crumbling"), layout_platform(600.0, 200.0, 200.0, "crumbling"), layout_platform(750.0, 150.0, 400.0, "plain")],
+                enemies: vec![SegmentSpawn {at: 100.0, name: "turret".to_string(), y: 700.0}, SegmentSpawn {at: 500.0, name: "turret".to_string(), y: 650.0}],
+                pickups: vec![SegmentSpawn {at: 750.0, name: "repair".to_string(), y: 200.0}],
+                triggers: vec![],
+            },
+            //a tower of one way platforms with flyers circling it and a reward at the top
+            Segment {
+                name: "tower".to_string(),
+                distance: 0.0,
+                length: 750.0,
+                random: true,
+                platforms: vec![layout_platform(0.0, 100.0, 450.0, "one way"), layout_platform(150.0, 250.0, 450.0, "one way"), layout_platform(300.0, 400.0, 450.0, "one way"), layout_platform(450.0, 550.0, 450.0, "one way"), layout_platform(600.0, 700.0, 400.0, "plain")],
+                enemies: vec![SegmentSpawn {at: 200.0, name: "flyer".to_string(), y: 500.0}, SegmentSpawn {at: 450.0, name: "flyer".to_string(), y: 300.0}],
+                pickups: vec![SegmentSpawn {at: 600.0, name: "overdrive".to_string(), y: 750.0}],
+                triggers: vec![],
+            },
+        ],
+        biomes: vec![
+            Biome {name: "outskirts".to_string(), distance: 0.0, layers: vec![BackgroundLayer {image: 0, source: "background".to_string(), rate: 0.1, y: 0.0, height: 900.0, colour: [1.0, 1.0, 1.0, 1.0]}, BackgroundLayer {image: 1, source: "platform".to_string(), rate: 0.5, y: 780.0, height: 120.0, colour: [0.3, 0.3, 0.4, 0.8]}], tint: [1.0, 1.0, 1.0, 1.0], platform_images: vec![0, 1, 2], enemies: vec!["drifter".to_string(), "turret".to_string(), "diver".to_string(), "tank".to_string()], rule: "none".to_string(), strength: 0.0},
+            //gravity is weaker, so jumps go higher and everything falls slower
+            Biome {name: "sky".to_string(), distance: 2000.0, layers: vec![BackgroundLayer {image: 1, source: "background".to_string(), rate: 0.05, y: 0.0, height: 900.0, colour: [1.0, 1.0, 1.0, 1.0]}, BackgroundLayer {image: 0, source: "background".to_string(), rate: 0.3, y: 0.0, height: 450.0, colour: [1.0, 1.0, 1.0, 0.3]}], tint: [0.8, 0.9, 1.0, 1.0], platform_images: vec![1, 2], enemies: vec!["drifter".to_string(), "diver".to_string(), "flyer".to_string(), "bomber".to_string()], rule: "low gravity".to_string(), strength: 0.4},
+            //a wind blows everything back towards the left of the screen
+            Biome {name: "storm".to_string(), distance: 5000.0, layers: vec![BackgroundLayer {image: 0, source: "background".to_string(), rate: 0.2, y: 0.0, height: 900.0, colour: [1.0, 1.0, 1.0, 1.0]}, BackgroundLayer {image: 2, source: "platform".to_string(), rate: 0.6, y: 800.0, height: 100.0, colour: [0.2, 0.2, 0.25, 0.9]}], tint: [0.5, 0.5, 0.6, 1.0], platform_images: vec![2, 0], enemies: vec!["drifter".to_string(), "turret".to_string(), "tank".to_string(), "bomber".to_string()], rule: "wind".to_string(), strength: 0.3},
+            //only the area around the player can be seen
+            Biome {name: "depths".to_string(), distance: 8000.0, layers: vec![BackgroundLayer {image: 2, source: "background".to_string(), rate: 0.1, y: 0.0, height: 900.0, colour: [1.0, 1.0, 1.0, 1.0]}, BackgroundLayer {image: 5, source: "platform".to_string(), rate: 0.4, y: 820.0, height: 80.0, colour: [0.2, 0.3, 0.4, 0.8]}], tint: [0.6, 0.7, 0.9, 1.0], platform_images: vec![2], enemies: vec!["diver".to_string(), "flyer".to_string(), "tank".to_string(), "bomber".to_string()], rule: "darkness".to_string(), strength: 250.0},
+        ],
+        particles: vec![],
+        damage_numbers: vec![],
+        editor: Editor {active: false, testing: false, scroll: 0.0, tool: 0, segment: Segment {name: "custom".to_string(), distance: 0.0, length: 0.0, random: true, platforms: vec![], enemies: vec![], pickups: vec![], triggers: vec![]}, drag: vec![], naming: false, message: "".to_string(), kept_storage: TimeLoopStorage {max_camera: 0.0, stored_enemies: vec![], stored_platforms: vec![], stored_segments: vec![], stored_boss_drops: vec![], stored_boss_fights: vec![], playing: false}},
     };
+    //add the segments made in the editor to the ones the generator can use
+    load_segments(&mut game);
+    //the set pieces are recorded from the start, so the first run plays them too
+    game.time_loop_storage.stored_segments = game.segments.iter().filter(|segment| !segment.random).cloned().collect();
+    for segment in game.time_loop_storage.stored_segments.iter_mut() {
+        roll_segment_kinds(segment);
+    }
+    game.time_loop_reading.stored_segments = game.time_loop_storage.stored_segments.clone();
+
+    //the artifact seed can be passed as the first argument, otherwise it is random
+    let seed = std::env::args().nth(1).and_then(|arg| arg.parse::<u64>().ok()).unwrap_or(rand::thread_rng().gen());
 
     let mut menu = Menu {
         pressed_space: false,
@@ -1559,84 +3872,252 @@ fn main() {
         quit: false,
         screen: 0,
         selected_vehicle: 0,
-        artifact1: Artifact {
-            name: "start1".to_string(),
-            description: "start1".to_string(),
-            image: 0,
-            modify_player: Rc::new(|player: &mut Player| {
-                player.health += 10.0;
-            }),
-        },
-        artifact2: Artifact {
-            name: "start2".to_string(),
-            description: "start2".to_string(),
-            image: 0,
-            modify_player: Rc::new(|player: &mut Player| {
-                player.speed += 10.0;
-            }),
-        },
-        artifact3: Artifact {
-            name: "start3".to_string(),
-            description: "start3".to_string(),
-            image: 0,
-            modify_player: Rc::new(|player: &mut Player| {
-                player.jump += 10.0;
-            }),
-        },
-        buttons: ButtonList {
-            buttons: vec![
-                CustomButton {x: 720.0, y: 450.0, width: 200.0, height: 50.0, action: Action {action: "play".to_string()}, image: 2, text_image: 100},
-                CustomButton {x: 720.0, y: 400.0, width: 200.0, height: 50.0, action: Action {action: "quit".to_string()}, image: 1, text_image: 4},
-                CustomButton {x: 720.0, y: 350.0, width: 200.0, height: 50.0, action: Action {action: "update vehicle".to_string()}, image: 13, text_image: 11},
-                CustomButton {x: 720.0, y: 300.0, width: 200.0, height: 50.0, action: Action {action: "new vehicle".to_string()}, image: 12, text_image: 6},
-                CustomButton {x: 720.0, y: 250.0, width: 200.0, height: 50.0, action: Action {action: "back".to_string()}, image: 0, text_image: 0},
-                CustomButton {x: 720.0, y: 200.0, width: 200.0, height: 50.0, action: Action {action: "artifact1".to_string()}, image: 12, text_image: 0},
-                CustomButton {x: 720.0, y: 150.0, width: 200.0, height: 50.0, action: Action {action: "artifact2".to_string()}, image: 12, text_image: 0},
-                CustomButton {x: 720.0, y: 100.0, width: 200.0, height: 50.0, action: Action {action: "artifact3".to_string()}, image: 12, text_image: 0},
-                CustomButton {x: 720.0, y: 50.0, width: 200.0, height: 50.0, action: Action {action: "health".to_string()}, image: 12, text_image: 9},
-                CustomButton {x: 720.0, y: 0.0, width: 200.0, height: 50.0, action: Action {action: "damage".to_string()}, image: 12, text_image: 8},
-                CustomButton {x: 720.0, y: 0.0, width: 200.0, height: 50.0, action: Action {action: "next vehicle".to_string()}, image: 11, text_image: 5},
-                CustomButton {x: 720.0, y: 0.0, width: 200.0, height: 50.0, action: Action {action: "previous vehicle".to_string()}, image: 11, text_image: 5},
-            ],
-        },
+        artifact_pool: vec![
+            Artifact {
+                name: "plating".to_string(),
+                description: "a little more health".to_string(),
+                modify_player: Rc::new(|player: &mut Player| {
+                    player.health += 10.0;
+                }),
+                stacks: 0,
+                max_stacks: 3,
+                affects_clones: false,
+                modify_clone: Rc::new(|_clone: &mut Player, _player: &Player| {}),
+                update_clones: Rc::new(|_state: &mut Game| {}),
+                rarity: 0,
+                weight: 1.0,
+                tags: vec!["health".to_string()],
+            },
+            Artifact {
+                name: "tuned engine".to_string(),
+                description: "a little faster".to_string(),
+                modify_player: Rc::new(|player: &mut Player| {
+                    player.speed += 0.1;
+                }),
+                stacks: 0,
+                max_stacks: 3,
+                affects_clones: false,
+                modify_clone: Rc::new(|_clone: &mut Player, _player: &Player| {}),
+                update_clones: Rc::new(|_state: &mut Game| {}),
+                rarity: 0,
+                weight: 1.0,
+                tags: vec!["mobility".to_string()],
+            },
+            Artifact {
+                name: "spring struts".to_string(),
+                description: "jumps a little higher".to_string(),
+                modify_player: Rc::new(|player: &mut Player| {
+                    player.jump += 5.0;
+                }),
+                stacks: 0,
+                max_stacks: 3,
+                affects_clones: false,
+                modify_clone: Rc::new(|_clone: &mut Player, _player: &Player| {}),
+                update_clones: Rc::new(|_state: &mut Game| {}),
+                rarity: 0,
+                weight: 1.0,
+                tags: vec!["mobility".to_string()],
+            },
+            Artifact {
+                name: "reinforced hull".to_string(),
+                description: "a lot more health".to_string(),
+                modify_player: Rc::new(|player: &mut Player| {
+                    player.health += 40.0;
+                }),
+                stacks: 0,
+                max_stacks: 1,
+                affects_clones: false,
+                modify_clone: Rc::new(|_clone: &mut Player, _player: &Player| {}),
+                update_clones: Rc::new(|_state: &mut Game| {}),
+                rarity: 1,
+                weight: 1.0,
+                tags: vec!["health".to_string()],
+            },
+            Artifact {
+                name: "overclocked barrel".to_string(),
+                description: "bullets do half again as much damage".to_string(),
+                modify_player: Rc::new(|player: &mut Player| {
+                    player.damage *= 1.5;
+                }),
+                stacks: 0,
+                max_stacks: 2,
+                affects_clones: false,
+                modify_clone: Rc::new(|_clone: &mut Player, _player: &Player| {}),
+                update_clones: Rc::new(|_state: &mut Game| {}),
+                rarity: 1,
+                weight: 1.0,
+                tags: vec!["damage".to_string()],
+            },
+            Artifact {
+                name: "scattergun".to_string(),
+                description: "swaps the vehicle's gun for one that fires three weaker shots at once".to_string(),
+                modify_player: Rc::new(|player: &mut Player| {
+                    //only vehicles that already shoot get the new gun
+                    if player.weapon.is_some() {
+                        player.weapon = Some(weapon("scattergun"));
+                    }
+                }),
+                stacks: 0,
+                max_stacks: 1,
+                affects_clones: false,
+                modify_clone: Rc::new(|_clone: &mut Player, _player: &Player| {}),
+                update_clones: Rc::new(|_state: &mut Game| {}),
+                rarity: 1,
+                weight: 0.8,
+                tags: vec!["weapon".to_string()],
+            },
+            Artifact {
+                name: "sharpened rounds".to_string(),
+                description: "a little more damage".to_string(),
+                modify_player: Rc::new(|player: &mut Player| {
+                    player.damage += 0.3;
+                }),
+                stacks: 0,
+                max_stacks: 3,
+                affects_clones: false,
+                modify_clone: Rc::new(|_clone: &mut Player, _player: &Player| {}),
+                update_clones: Rc::new(|_state: &mut Game| {}),
+                rarity: 0,
+                weight: 1.0,
+                tags: vec!["damage".to_string()],
+            },
+            Artifact {
+                name: "chrono core".to_string(),
+                description: "more health, speed and jump".to_string(),
+                modify_player: Rc::new(|player: &mut Player| {
+                    player.health += 25.0;
+                    player.speed += 0.3;
+                    player.jump += 10.0;
+                }),
+                stacks: 0,
+                max_stacks: 1,
+                affects_clones: false,
+                modify_clone: Rc::new(|_clone: &mut Player, _player: &Player| {}),
+                update_clones: Rc::new(|_state: &mut Game| {}),
+                rarity: 2,
+                weight: 1.0,
+                tags: vec!["health".to_string(), "mobility".to_string()],
+            },
+            Artifact {
+                name: "echo rounds".to_string(),
+                description: "clones from earlier loops do more damage".to_string(),
+                modify_player: Rc::new(|_player: &mut Player| {}),
+                stacks: 0,
+                max_stacks: 2,
+                affects_clones: true,
+                modify_clone: Rc::new(|clone: &mut Player, _player: &Player| {
+                    clone.damage *= 1.5;
+                }),
+                update_clones: Rc::new(|_state: &mut Game| {}),
+                rarity: 1,
+                weight: 1.0,
+                tags: vec!["time".to_string(), "damage".to_string()],
+            },
+            Artifact {
+                name: "temporal shield".to_string(),
+                description: "clones absorb enemy bullets that come near them".to_string(),
+                modify_player: Rc::new(|_player: &mut Player| {}),
+                stacks: 0,
+                max_stacks: 1,
+                affects_clones: false,
+                modify_clone: Rc::new(|_clone: &mut Player, _player: &Player| {}),
+                update_clones: Rc::new(|state: &mut Game| {
+                    //a clone takes the hits from bullets fired at it, or at no one, as they come near it, and isn't hurt by them while it is invulnerable
+                    for bullet in state.enemy_bullets.bullets.clone() {
+                        for clone in state.clones.players.iter_mut().filter(|clone| clone.active) {
+                            let target = &bullet.data_string[0];
+                            if (target.is_empty() || *target == clone.id) && hit_agent(clone, bullet.x, bullet.y, 120.0, 120.0, bullet.damage, &mut state.damage_numbers) {
+                                let remove = state.enemy_bullets.remove.clone();
+                                (remove)(bullet.id, state);
+                                break;
+                            }
+                        }
+                    }
+                }),
+                rarity: 1,
+                weight: 1.0,
+                tags: vec!["time".to_string(), "health".to_string()],
+            },
+            Artifact {
+                name: "second chance".to_string(),
+                description: "the oldest clone comes back once when it is destroyed".to_string(),
+                modify_player: Rc::new(|_player: &mut Player| {}),
+                stacks: 0,
+                max_stacks: 1,
+                affects_clones: false,
+                modify_clone: Rc::new(|_clone: &mut Player, _player: &Player| {}),
+                update_clones: Rc::new(|state: &mut Game| {
+                    //the oldest clone is the one recorded in the earliest loop
+                    let oldest = state.clones.players.iter().map(|clone| clone.loop_made).min();
+                    for clone in state.clones.players.iter_mut() {
+                        if Some(clone.loop_made) == oldest && !clone.active && !clone.revived {
+                            //revive it where it died with the health it started with, keeping its place in the recording and everything applied to it
+                            clone.health = clone.max_health;
+                            clone.active = true;
+                            clone.revived = true;
+                        }
+                    }
+                }),
+                rarity: 2,
+                weight: 1.0,
+                tags: vec!["time".to_string()],
+            },
+            Artifact {
+                name: "hand-me-downs".to_string(),
+                description: "clones use the player's damage if it is higher than their own".to_string(),
+                modify_player: Rc::new(|_player: &mut Player| {}),
+                stacks: 0,
+                max_stacks: 1,
+                affects_clones: true,
+                modify_clone: Rc::new(|clone: &mut Player, player: &Player| {
+                    clone.damage = clone.damage.max(player.damage);
+                }),
+                update_clones: Rc::new(|_state: &mut Game| {}),
+                rarity: 0,
+                weight: 1.0,
+                tags: vec!["time".to_string(), "damage".to_string()],
+            },
+        ],
+        artifact_choices: vec![],
+        rng: StdRng::seed_from_u64(seed),
         button_screens: ButtonListList {
             button_lists: vec![
                 ButtonList {
                     buttons: vec![
-                        CustomButton {x: 220.0, y: 150.0, width: 200.0, height: 200.0, action: Action {action: "artifact1".to_string()}, image: 12, text_image: 100},
-                        CustomButton {x: 720.0, y: 150.0, width: 200.0, height: 200.0, action: Action {action: "artifact2".to_string()}, image: 12, text_image: 100},
-                        CustomButton {x: 1220.0, y: 150.0, width: 200.0, height: 200.0, action: Action {action: "artifact3".to_string()}, image: 12, text_image: 100},
+                        CustomButton {x: 220.0, y: 150.0, width: 200.0, height: 200.0, action: Action {action: "artifact".to_string(), index: 0}, image: 12, text_image: 100},
+                        CustomButton {x: 720.0, y: 150.0, width: 200.0, height: 200.0, action: Action {action: "artifact".to_string(), index: 1}, image: 12, text_image: 100},
+                        CustomButton {x: 1220.0, y: 150.0, width: 200.0, height: 200.0, action: Action {action: "artifact".to_string(), index: 2}, image: 12, text_image: 100},
                     ],
                 },
                 ButtonList {
                     buttons: vec![
-                        CustomButton {x: 520.0, y: 450.0, width: 200.0, height: 200.0, action: Action {action: "update vehicle".to_string()}, image: 13, text_image: 11},
-                        CustomButton {x: 920.0, y: 450.0, width: 200.0, height: 200.0, action: Action {action: "new vehicle".to_string()}, image: 12, text_image: 6},
+                        CustomButton {x: 520.0, y: 450.0, width: 200.0, height: 200.0, action: Action {action: "update vehicle".to_string(), index: 0}, image: 13, text_image: 11},
+                        CustomButton {x: 920.0, y: 450.0, width: 200.0, height: 200.0, action: Action {action: "new vehicle".to_string(), index: 0}, image: 12, text_image: 6},
+                        CustomButton {x: 720.0, y: 200.0, width: 200.0, height: 100.0, action: Action {action: "drop artifact".to_string(), index: 0}, image: 0, text_image: 100},
+                        CustomButton {x: 1320.0, y: 80.0, width: 150.0, height: 100.0, action: Action {action: "editor".to_string(), index: 0}, image: 0, text_image: 100},
                     ]
                 },
                 ButtonList {
                     buttons: vec![
-                        CustomButton {x: 520.0, y: 450.0, width: 200.0, height: 200.0, action: Action {action: "health".to_string()}, image: 12, text_image: 9},
-                        CustomButton {x: 920.0, y: 450.0, width: 200.0, height: 200.0, action: Action {action: "damage".to_string()}, image: 12, text_image: 8},
-                        CustomButton {x: 720.0, y: 300.0, width: 200.0, height: 200.0, action: Action {action: "replay".to_string()}, image: 2, text_image: 100},
+                        CustomButton {x: 320.0, y: 450.0, width: 200.0, height: 200.0, action: Action {action: "upgrade".to_string(), index: 0}, image: 12, text_image: 100},
+                        CustomButton {x: 720.0, y: 550.0, width: 200.0, height: 200.0, action: Action {action: "upgrade".to_string(), index: 1}, image: 12, text_image: 100},
+                        CustomButton {x: 1120.0, y: 450.0, width: 200.0, height: 200.0, action: Action {action: "upgrade".to_string(), index: 2}, image: 13, text_image: 100},
+                        CustomButton {x: 720.0, y: 300.0, width: 200.0, height: 200.0, action: Action {action: "replay".to_string(), index: 0}, image: 2, text_image: 100},
                     ]
                 },
                 ButtonList {
                     buttons: vec![
-                        CustomButton {x: 1120.0, y: 450.0, width: 200.0, height: 200.0, action: Action {action: "next vehicle".to_string()}, image: 11, text_image: 5},
-                        CustomButton {x: 320.0, y: 450.0, width: 200.0, height: 200.0, action: Action {action: "previous vehicle".to_string()}, image: 11, text_image: 5},
-                        CustomButton {x: 720.0, y: 250.0, width: 200.0, height: 200.0, action: Action {action: "newplay".to_string()}, image: 2, text_image: 100},
+                        CustomButton {x: 1120.0, y: 450.0, width: 200.0, height: 200.0, action: Action {action: "next vehicle".to_string(), index: 0}, image: 11, text_image: 5},
+                        CustomButton {x: 320.0, y: 450.0, width: 200.0, height: 200.0, action: Action {action: "previous vehicle".to_string(), index: 0}, image: 11, text_image: 5},
+                        CustomButton {x: 720.0, y: 250.0, width: 200.0, height: 200.0, action: Action {action: "newplay".to_string(), index: 0}, image: 2, text_image: 100},
                     ]
                 }
             ]
         },
-        health_modifier: 0,
-        damage_modifier: 0,
         artifacts: vec![],
-        health_boost: 0,
-        damage_boost: 0,
+        last_artifact: "".to_string(),
         vehicles: vec![
-            //relatively mobile, low health, low damage, no ability
+            //relatively mobile, low health, low damage, can dash
             Player {
                 damage: 1.0,
                 id: "Base".to_string(),
@@ -1649,7 +4130,7 @@ fn main() {
                 jump: 50.0,
                 data_bool: vec![true, false, false],
                 data_string: vec![],
-                data_num: vec![0.0, 0.0, 0.0],
+                data_num: vec![0.0, 0.0, 0.0, 1.0],
                 moves: KeySequence {sequence: vec![], step: 0, length: 0},
                 apply_inputs: Rc::new(|state: &mut Game| {
                     if state.player.moves.sequence[state.player.moves.step as usize].a {
@@ -1661,314 +4142,35 @@ fn main() {
                     if state.player.moves.sequence[state.player.moves.step as usize].d {
                         state.player.data_num[0] += state.player.speed;
                     }
-                    if state.player.moves.sequence[state.player.moves.step as usize].w {
-                        if state.player.data_bool[2] {
+                    if state.player.moves.sequence[state.player.moves.step as usize].w
+                        && state.player.data_bool[2] {
                             state.player.data_num[1] = state.player.data_num[1].max(state.player.jump);
                         }
-                    }
                     if state.player.moves.sequence[state.player.moves.step as usize].special {
                         if !state.player.data_bool[1] {
                             state.player.data_bool[1] = true;
                             //invert the shooting boolean
-                            state.player.data_bool[0] = !state.player.data_bool[0];
-                        }
-                    } else {
-                        state.player.data_bool[1] = false;
-                    }
-                    //shooting
-                    if state.player.data_bool[0] && state.player.data_num[2] <= 0.0 {
-                        state.player.data_num[2] = 1.0;
-                        //find all used IDs
-                        let mut used_ids = vec![];
-                        for bullet in state.player_bullets.bullets.clone() {
-                            used_ids.push(bullet.id);
-                        }
-                        //use a new ID
-                        let mut new_id = 0;
-                        while used_ids.contains(&new_id) {
-                            new_id += 1;
-                        }
-                        //add a new bullet
-                        let add = state.player_bullets.add.clone();
-                        (add)(Bullet {x: state.player.x, y: state.player.y, width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: state.player.damage, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
-                            let get = state.player_bullets.get.clone();
-                            let mut bullet = (get)(id, state);
-                            bullet.x += 10.0;
-                            let remove = state.player_bullets.remove.clone();
-                            (remove)(id, state);
-                            let add = state.player_bullets.add.clone();
-                            let overlap = state.platforms.platforms.iter().filter(|platform| {
-                                bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
-                            }).collect::<Vec<&Platform>>();
-                            //check for enemy collisions
-                            let mut hit_enemies = vec![];
-                            for mut enemy in state.enemies.enemies.iter_mut() {
-                                if bullet.x + bullet.width/2.0 > enemy.x - enemy.width/2.0 && bullet.x - bullet.width/2.0 < enemy.x + enemy.width/2.0 && bullet.y + bullet.height/2.0 > enemy.y - enemy.height/2.0 && bullet.y - bullet.height/2.0 < enemy.y + enemy.height/2.0 {
-                                    enemy.health -= bullet.damage;
-                                    hit_enemies.push(enemy.id);
-                                }
-                            }
-                            if overlap.len() == 0 && hit_enemies.len() == 0 {
-                                (add)(bullet, state);
-                            }
-                        }), id: new_id, image: 0}, state);
-                    }
-                    //apply air resistance
-                    state.player.data_num[0] *= 0.9;
-                    state.player.data_num[1] *= 0.9;
-                    //apply gravity
-                    state.player.data_num[1] -= 1.0;
-                    //apply bullet cooldown
-                    if state.player.data_num[2] > 0.0 {
-                        state.player.data_num[2] -= 0.05;
-                    }
-                    state.player.data_bool[2] = false;
-                    //check for platform collisions
-                    for platform in state.platforms.platforms.iter() {
-                        if (state.player.x - platform.x).abs() < platform.width/2.0 + state.player.width/2.0 && (state.player.y - platform.y).abs() < platform.height/2.0 + state.player.height/2.0 {
-                            //check which side of the platform is closest
-                            let left_overlap = (state.player.x + state.player.width/2.0) - (platform.x - platform.width/2.0);
-                            let right_overlap = (platform.x + platform.width/2.0) - (state.player.x - state.player.width/2.0);
-                            let bottom_overlap = (state.player.y + state.player.height/2.0) - (platform.y - platform.height/2.0);
-                            let top_overlap = (platform.y + platform.height/2.0) - (state.player.y - state.player.height/2.0);
-                            //find the smallest overlap
-                            let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
-                            //apply the smallest overlap
-                            if smallest_overlap == left_overlap {
-                                state.player.data_num[0] = state.player.data_num[0].min(0.0);
-                                state.player.x = platform.x - platform.width/2.0 - state.player.width/2.0 + 1.0;
-                            } else if smallest_overlap == right_overlap {
-                                state.player.data_num[0] = state.player.data_num[0].max(0.0);
-                                state.player.x = platform.x + platform.width/2.0 + state.player.width/2.0 - 1.0;
-                            } else if smallest_overlap == top_overlap {
-                                state.player.data_num[1] = state.player.data_num[1].max(0.0);
-                                state.player.y = platform.y + platform.height/2.0 + state.player.height/2.0 - 1.0;
-                                state.player.data_bool[2] = true;
-                            } else if smallest_overlap == bottom_overlap {
-                                state.player.data_num[1] = state.player.data_num[1].min(0.0);
-                                state.player.y = platform.y - platform.height/2.0 - state.player.height/2.0 + 1.0;
-                            }
-                        }
-                    }
-                    //check for ground collisions
-                    if state.player.y - state.player.height/2.0 < 0.0 {
-                        state.player.data_bool[2] = true;
-                        state.player.data_num[1] = state.player.data_num[1].max(0.0);
-                        state.player.y = state.player.height/2.0 - 1.0;
-                    }
-                    //move the player
-                    state.player.x += state.player.data_num[0];
-                    state.player.y += state.player.data_num[1];
-                    //update the step
-                    if state.player.moves.step < state.player.moves.length-1 {
-                        state.player.moves.step += 1;
-                    }
-                }),
-                apply_inputs_as_clone: Rc::new(|state: &mut Game, id: String| {
-                    let get = state.clones.get.clone();
-                    let mut clone = (get)(id, state);
-                    if clone.moves.sequence[clone.moves.step as usize].a {
-                        clone.data_num[0] -= clone.speed;
-                    }
-                    if clone.moves.sequence[clone.moves.step as usize].s {
-                        clone.data_num[1] = clone.data_num[1].min(0.0);
-                    }
-                    if clone.moves.sequence[clone.moves.step as usize].d {
-                        clone.data_num[0] += clone.speed;
-                    }
-                    if clone.moves.sequence[clone.moves.step as usize].w {
-                        if clone.data_bool[2] {
-                            clone.data_num[1] = clone.data_num[1].max(clone.jump);
-                        }
-                    }
-                    if clone.moves.sequence[clone.moves.step as usize].special {
-                        if !clone.data_bool[1] {
-                            clone.data_bool[1] = true;
-                            //invert the shooting boolean
-                            clone.data_bool[0] = !clone.data_bool[0];
-                        }
-                    } else {
-                        clone.data_bool[1] = false;
-                    }
-                    //shooting
-                    if clone.data_bool[0] && clone.data_num[2] <= 0.0 {
-                        clone.data_num[2] = 1.0;
-                        //find all used IDs
-                        let mut used_ids = vec![];
-                        for bullet in state.player_bullets.bullets.clone() {
-                            used_ids.push(bullet.id);
-                        }
-                        //use a new ID
-                        let mut new_id = 0;
-                        while used_ids.contains(&new_id) {
-                            new_id += 1;
-                        }
-                        //add a new bullet
-                        let add = state.player_bullets.add.clone();
-                        (add)(Bullet {x: clone.x, y: clone.y, width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: clone.damage, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
-                            let get = state.player_bullets.get.clone();
-                            let mut bullet = (get)(id, state);
-                            bullet.x += 10.0;
-                            let remove = state.player_bullets.remove.clone();
-                            (remove)(id, state);
-                            let add = state.player_bullets.add.clone();
-                            let overlap = state.platforms.platforms.iter().filter(|platform| {
-                                bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
-                            }).collect::<Vec<&Platform>>();
-                            //check for enemy collisions
-                            let mut hit_enemies = vec![];
-                            for mut enemy in state.enemies.enemies.iter_mut() {
-                                if bullet.x + bullet.width/2.0 > enemy.x - enemy.width/2.0 && bullet.x - bullet.width/2.0 < enemy.x + enemy.width/2.0 && bullet.y + bullet.height/2.0 > enemy.y - enemy.height/2.0 && bullet.y - bullet.height/2.0 < enemy.y + enemy.height/2.0 {
-                                    enemy.health -= bullet.damage;
-                                    hit_enemies.push(enemy.id);
-                                }
-                            }
-                            if overlap.len() == 0 && hit_enemies.len() == 0 {
-                                (add)(bullet, state);
-                            }
-                        }), id: new_id, image: 0}, state);
-                    }
-                    //apply air resistance
-                    clone.data_num[0] *= 0.9;
-                    clone.data_num[1] *= 0.9;
-                    //apply gravity
-                    clone.data_num[1] -= 1.0;
-                    //apply bullet cooldown
-                    if clone.data_num[2] > 0.0 {
-                        clone.data_num[2] -= 0.05;
-                    }
-                    clone.data_bool[2] = false;
-                    //check for platform collisions
-                    for platform in state.platforms.platforms.iter() {
-                        if (clone.x - platform.x).abs() < platform.width/2.0 + clone.width/2.0 && (clone.y - platform.y).abs() < platform.height/2.0 + clone.height/2.0 {
-                            //check which side of the platform is closest
-                            let left_overlap = (clone.x + clone.width/2.0) - (platform.x - platform.width/2.0);
-                            let right_overlap = (platform.x + platform.width/2.0) - (clone.x - clone.width/2.0);
-                            let bottom_overlap = (clone.y + clone.height/2.0) - (platform.y - platform.height/2.0);
-                            let top_overlap = (platform.y + platform.height/2.0) - (clone.y - clone.height/2.0);
-                            //find the smallest overlap
-                            let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
-                            //apply the smallest overlap
-                            if smallest_overlap == left_overlap {
-                                clone.data_num[0] = clone.data_num[0].min(0.0);
-                                clone.x = platform.x - platform.width/2.0 - clone.width/2.0 + 1.0;
-                            } else if smallest_overlap == right_overlap {
-                                clone.data_num[0] = clone.data_num[0].max(0.0);
-                                clone.x = platform.x + platform.width/2.0 + clone.width/2.0 - 1.0;
-                            } else if smallest_overlap == top_overlap {
-                                clone.data_num[1] = clone.data_num[1].max(0.0);
-                                clone.y = platform.y + platform.height/2.0 + clone.height/2.0 - 1.0;
-                                clone.data_bool[2] = true;
-                            } else if smallest_overlap == bottom_overlap {
-                                clone.data_num[1] = clone.data_num[1].min(0.0);
-                                clone.y = platform.y - platform.height/2.0 - clone.height/2.0 + 1.0;
-                            }
-                        }
-                    }
-                    //check for ground collisions
-                    if clone.y - clone.height/2.0 < 0.0 {
-                        clone.data_bool[2] = true;
-                        clone.data_num[1] = clone.data_num[1].max(0.0);
-                        clone.y = clone.height/2.0 - 1.0;
-                    }
-                    //move the player
-                    clone.x += clone.data_num[0];
-                    clone.y += clone.data_num[1];
-                    //update the step
-                    if clone.moves.step < clone.moves.length-1 {
-                        clone.moves.step += 1;
-                    }
-                    let add = state.clones.add.clone();
-                    (add)(clone, state);
-                }),
-                reset: Rc::new(|player: &mut Player| {
-                    player.x = 0.0;
-                    player.y = 0.0;
-                    player.health = 100.0;
-                    player.data_bool = vec![true, false, false];
-                    player.data_string = vec![];
-                    player.data_num = vec![0.0, 0.0, 0.0];
-                }),
-                active: true,
-                image: 2,
-            },
-            //high health, collision damage, slow, heals
-            Player {
-                damage: 0.0,
-                id: "Smasher".to_string(),
-                x: 0.0,
-                y: 0.0,
-                width: 50.0,
-                height: 60.0,
-                health: 500.0,
-                speed: 0.5,
-                jump: 40.0,
-                data_bool: vec![true, false, false],
-                data_string: vec![],
-                data_num: vec![0.0, 0.0, 0.0],
-                moves: KeySequence {sequence: vec![], step: 0, length: 0},
-                apply_inputs: Rc::new(|state: &mut Game| {
-                    if state.player.moves.sequence[state.player.moves.step as usize].a {
-                        state.player.data_num[0] -= state.player.speed;
-                    }
-                    if state.player.moves.sequence[state.player.moves.step as usize].s {
-                        state.player.data_num[1] = state.player.data_num[1].min(0.0);
-                    }
-                    if state.player.moves.sequence[state.player.moves.step as usize].d {
-                        state.player.data_num[0] += state.player.speed;
-                    }
-                    if state.player.moves.sequence[state.player.moves.step as usize].w {
-                        if state.player.data_bool[2] {
-                            state.player.data_num[1] = state.player.data_num[1].max(state.player.jump);
-                        }
-                    }
-                    if state.player.moves.sequence[state.player.moves.step as usize].special {
-                        //stop moving, but slowly regenerate health
-                        state.player.speed = 0.0;
-                        if state.player.health < 500.0 {
-                            state.player.health += 0.01;
+                            state.player.data_bool[0] = !state.player.data_bool[0];
                         }
                     } else {
-                        state.player.speed = 0.5;
+                        state.player.data_bool[1] = false;
                     }
-                    //no shooting for the smasher, they are melee
+                    //shooting
+                    let mut weapon = state.player.weapon.take();
+                    if let Some(weapon) = weapon.as_mut() {
+                        if weapon_ready(weapon, state.player.data_bool[0]) {
+                            fire_weapon(weapon, state.player.x, state.player.y, state.player.moves.sequence[state.player.moves.step as usize].aim, state.player.damage, "player", state);
+                        }
+                    }
+                    state.player.weapon = weapon;
                     //apply air resistance
                     state.player.data_num[0] *= 0.9;
                     state.player.data_num[1] *= 0.9;
                     //apply gravity
                     state.player.data_num[1] -= 1.0;
-                    //apply bullet cooldown
-                    if state.player.data_num[2] > 0.0 {
-                        state.player.data_num[2] -= 0.05;
-                    }
                     state.player.data_bool[2] = false;
                     //check for platform collisions
-                    for platform in state.platforms.platforms.iter() {
-                        if (state.player.x - platform.x).abs() < platform.width/2.0 + state.player.width/2.0 && (state.player.y - platform.y).abs() < platform.height/2.0 + state.player.height/2.0 {
-                            //check which side of the platform is closest
-                            let left_overlap = (state.player.x + state.player.width/2.0) - (platform.x - platform.width/2.0);
-                            let right_overlap = (platform.x + platform.width/2.0) - (state.player.x - state.player.width/2.0);
-                            let bottom_overlap = (state.player.y + state.player.height/2.0) - (platform.y - platform.height/2.0);
-                            let top_overlap = (platform.y + platform.height/2.0) - (state.player.y - state.player.height/2.0);
-                            //find the smallest overlap
-                            let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
-                            //apply the smallest overlap
-                            if smallest_overlap == left_overlap {
-                                state.player.data_num[0] = state.player.data_num[0].min(0.0);
-                                state.player.x = platform.x - platform.width/2.0 - state.player.width/2.0 + 1.0;
-                            } else if smallest_overlap == right_overlap {
-                                state.player.data_num[0] = state.player.data_num[0].max(0.0);
-                                state.player.x = platform.x + platform.width/2.0 + state.player.width/2.0 - 1.0;
-                            } else if smallest_overlap == top_overlap {
-                                state.player.data_num[1] = state.player.data_num[1].max(0.0);
-                                state.player.y = platform.y + platform.height/2.0 + state.player.height/2.0 - 1.0;
-                                state.player.data_bool[2] = true;
-                            } else if smallest_overlap == bottom_overlap {
-                                state.player.data_num[1] = state.player.data_num[1].min(0.0);
-                                state.player.y = platform.y - platform.height/2.0 - state.player.height/2.0 + 1.0;
-                            }
-                        }
-                    }
+                    collide_platforms(&mut state.player, &state.platforms.platforms);
                     //check for ground collisions
                     if state.player.y - state.player.height/2.0 < 0.0 {
                         state.player.data_bool[2] = true;
@@ -1995,58 +4197,35 @@ fn main() {
                     if clone.moves.sequence[clone.moves.step as usize].d {
                         clone.data_num[0] += clone.speed;
                     }
-                    if clone.moves.sequence[clone.moves.step as usize].w {
-                        if clone.data_bool[2] {
+                    if clone.moves.sequence[clone.moves.step as usize].w
+                        && clone.data_bool[2] {
                             clone.data_num[1] = clone.data_num[1].max(clone.jump);
                         }
-                    }
                     if clone.moves.sequence[clone.moves.step as usize].special {
-                        //stop moving, but slowly regenerate health
-                        clone.speed = 0.0;
-                        if clone.health < 500.0 {
-                            clone.health += 0.01;
+                        if !clone.data_bool[1] {
+                            clone.data_bool[1] = true;
+                            //invert the shooting boolean
+                            clone.data_bool[0] = !clone.data_bool[0];
                         }
                     } else {
-                        clone.speed = 0.5;
+                        clone.data_bool[1] = false;
                     }
-                    //no shooting for the smasher, they are melee
+                    //shooting
+                    let mut weapon = clone.weapon.take();
+                    if let Some(weapon) = weapon.as_mut() {
+                        if weapon_ready(weapon, clone.data_bool[0]) {
+                            fire_weapon(weapon, clone.x, clone.y, clone.moves.sequence[clone.moves.step as usize].aim, clone.damage, "clone", state);
+                        }
+                    }
+                    clone.weapon = weapon;
                     //apply air resistance
                     clone.data_num[0] *= 0.9;
                     clone.data_num[1] *= 0.9;
                     //apply gravity
                     clone.data_num[1] -= 1.0;
-                    //apply bullet cooldown
-                    if clone.data_num[2] > 0.0 {
-                        clone.data_num[2] -= 0.05;
-                    }
                     clone.data_bool[2] = false;
                     //check for platform collisions
-                    for platform in state.platforms.platforms.iter() {
-                        if (clone.x - platform.x).abs() < platform.width/2.0 + clone.width/2.0 && (clone.y - platform.y).abs() < platform.height/2.0 + clone.height/2.0 {
-                            //check which side of the platform is closest
-                            let left_overlap = (clone.x + clone.width/2.0) - (platform.x - platform.width/2.0);
-                            let right_overlap = (platform.x + platform.width/2.0) - (clone.x - clone.width/2.0);
-                            let bottom_overlap = (clone.y + clone.height/2.0) - (platform.y - platform.height/2.0);
-                            let top_overlap = (platform.y + platform.height/2.0) - (clone.y - clone.height/2.0);
-                            //find the smallest overlap
-                            let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
-                            //apply the smallest overlap
-                            if smallest_overlap == left_overlap {
-                                clone.data_num[0] = clone.data_num[0].min(0.0);
-                                clone.x = platform.x - platform.width/2.0 - clone.width/2.0 + 1.0;
-                            } else if smallest_overlap == right_overlap {
-                                clone.data_num[0] = clone.data_num[0].max(0.0);
-                                clone.x = platform.x + platform.width/2.0 + clone.width/2.0 - 1.0;
-                            } else if smallest_overlap == top_overlap {
-                                clone.data_num[1] = clone.data_num[1].max(0.0);
-                                clone.y = platform.y + platform.height/2.0 + clone.height/2.0 - 1.0;
-                                clone.data_bool[2] = true;
-                            } else if smallest_overlap == bottom_overlap {
-                                clone.data_num[1] = clone.data_num[1].min(0.0);
-                                clone.y = platform.y - platform.height/2.0 - clone.height/2.0 + 1.0;
-                            }
-                        }
-                    }
+                    collide_platforms(&mut clone, &state.platforms.platforms);
                     //check for ground collisions
                     if clone.y - clone.height/2.0 < 0.0 {
                         clone.data_bool[2] = true;
@@ -2069,25 +4248,74 @@ fn main() {
                     player.health = 100.0;
                     player.data_bool = vec![true, false, false];
                     player.data_string = vec![];
-                    player.data_num = vec![0.0, 0.0, 0.0];
+                    player.data_num = vec![0.0, 0.0, 0.0, 1.0];
                 }),
                 active: true,
-                image: 0,
+                image: 2,
+                units: vec![],
+                buffs: vec![],
+                invulnerable: 0.0,
+                flash: 0.0,
+                animation: "idle".to_string(),
+                animation_time: 0.0,
+                tilt: 0.0,
+                max_health: 0.0,
+                revived: false,
+                ability: Some(ability("dash")),
+                weapon: Some(weapon("blaster")),
+                loop_made: 0,
+                upgrades: vec![
+                    Upgrade {
+                        name: "armour".to_string(),
+                        level: 0,
+                        max_level: 5,
+                        cost: 20,
+                        requires: "".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            player.health += 10.0*level as f64;
+                        }),
+                    },
+                    Upgrade {
+                        name: "weapons".to_string(),
+                        level: 0,
+                        max_level: 5,
+                        cost: 25,
+                        requires: "armour".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            player.damage *= 1.0 + 0.2*level as f64;
+                        }),
+                    },
+                    Upgrade {
+                        name: "rapid fire".to_string(),
+                        level: 0,
+                        max_level: 4,
+                        cost: 40,
+                        requires: "weapons".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            //the time between shots
+                            if let Some(weapon) = player.weapon.as_mut() {
+                                weapon.fire_rate *= 0.85f64.powi(level as i32);
+                            }
+                        }),
+                    },
+                ],
+                vehicle: 0,
+                artifacts: vec![],
             },
-            //high damage arcing projectiles, low health, slow, shoots explosive projectile on ability
+            //high health, collision damage, slow, heals
             Player {
-                damage: 100.0,
-                id: "Cannoneer".to_string(),
+                damage: 0.0,
+                id: "Smasher".to_string(),
                 x: 0.0,
                 y: 0.0,
                 width: 50.0,
                 height: 60.0,
-                health: 70.0,
+                health: 500.0,
                 speed: 0.5,
-                jump: 50.0,
+                jump: 40.0,
                 data_bool: vec![true, false, false],
                 data_string: vec![],
-                data_num: vec![0.0, 0.0, 0.0, 0.0],
+                data_num: vec![0.0, 0.0, 0.0, 0.01],
                 moves: KeySequence {sequence: vec![], step: 0, length: 0},
                 apply_inputs: Rc::new(|state: &mut Game| {
                     if state.player.moves.sequence[state.player.moves.step as usize].a {
@@ -2099,102 +4327,32 @@ fn main() {
                     if state.player.moves.sequence[state.player.moves.step as usize].d {
                         state.player.data_num[0] += state.player.speed;
                     }
-                    if state.player.moves.sequence[state.player.moves.step as usize].w {
-                        if state.player.data_bool[2] {
+                    if state.player.moves.sequence[state.player.moves.step as usize].w
+                        && state.player.data_bool[2] {
                             state.player.data_num[1] = state.player.data_num[1].max(state.player.jump);
                         }
-                    }
                     if state.player.moves.sequence[state.player.moves.step as usize].special {
-                        if !state.player.data_bool[1] {
-                            if state.player.data_num[2] <= 0.0 {
-                                state.player.data_bool[1] = true;
-                                //shoot an explosive arcing projectile
-                                //find all used IDs
-                                let mut used_ids = vec![];
-                                for bullet in state.player_bullets.bullets.clone() {
-                                    used_ids.push(bullet.id);
-                                }
-                                //use a new ID
-                                let mut new_id = 0;
-                                while used_ids.contains(&new_id) {
-                                    new_id += 1;
-                                }
-                                //add a new bullet
-                                let add = state.player_bullets.add.clone();
-                                (add)(Bullet {x: state.player.x, y: state.player.y, width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: state.player.damage, data_bool: vec![], data_string: vec![], data_num: vec![3.0, 6.0], update: Rc::new(|id: u32, mut state: &mut Game| {
-                                    let get = state.player_bullets.get.clone();
-                                    let mut bullet = (get)(id, state);
-                                    bullet.x += bullet.data_num[0];
-                                    bullet.y += bullet.data_num[1];
-                                    bullet.data_num[1] -= 1.0/15.0;
-                                    let remove = state.player_bullets.remove.clone();
-                                    (remove)(id, state);
-                                    let add = state.player_bullets.add.clone();
-                                    let overlap = state.platforms.platforms.iter().filter(|platform| {
-                                        bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
-                                    }).collect::<Vec<&Platform>>();
-                                    //check for enemy collisions
-                                    let mut hit_enemies = vec![];
-                                    for mut enemy in state.enemies.enemies.iter_mut() {
-                                        if bullet.x + bullet.width/2.0 > enemy.x - enemy.width/2.0 && bullet.x - bullet.width/2.0 < enemy.x + enemy.width/2.0 && bullet.y + bullet.height/2.0 > enemy.y - enemy.height/2.0 && bullet.y - bullet.height/2.0 < enemy.y + enemy.height/2.0 {
-                                            enemy.health -= bullet.damage;
-                                            hit_enemies.push(enemy.id);
-                                        }
-                                    }
-                                    if overlap.len() == 0 && hit_enemies.len() == 0 {
-                                        (add)(bullet, state);
-                                    } else {
-                                        //HUGE explosion, damaging all enemies near the bullet
-                                        for mut enemy in state.enemies.enemies.iter_mut() {
-                                            if (bullet.x - enemy.x).abs() < 300.0 && (bullet.y - enemy.y).abs() < 300.0 {
-                                                enemy.health -= bullet.damage;
-                                            }
-                                        }
-                                    }
-                                }), id: new_id, image: 0}, state);
-                                state.player.data_num[2] = 5.0;
-                            }
-                        } else {
-                            state.player.data_bool[1] = false;
+                        //stop moving, but slowly regenerate health
+                        state.player.speed = 0.0;
+                        if state.player.health < 500.0 {
+                            state.player.health += state.player.data_num[3];
                         }
+                    } else {
+                        state.player.speed = 0.5;
                     }
-                    state.player.data_bool[2] = false;
-                    if state.player.data_num[2] > 0.0 {
-                        state.player.data_num[2] -= 0.05;
-                    }
-                    //no basic shooting for the cannoneer
+                    //no shooting for the smasher, they are melee
                     //apply air resistance
                     state.player.data_num[0] *= 0.9;
                     state.player.data_num[1] *= 0.9;
                     //apply gravity
                     state.player.data_num[1] -= 1.0;
-                    //check for platform collisions
-                    for platform in state.platforms.platforms.iter() {
-                        if (state.player.x - platform.x).abs() < platform.width/2.0 + state.player.width/2.0 && (state.player.y - platform.y).abs() < platform.height/2.0 + state.player.height/2.0 {
-                            //check which side of the platform is closest
-                            let left_overlap = (state.player.x + state.player.width/2.0) - (platform.x - platform.width/2.0);
-                            let right_overlap = (platform.x + platform.width/2.0) - (state.player.x - state.player.width/2.0);
-                            let bottom_overlap = (state.player.y + state.player.height/2.0) - (platform.y - platform.height/2.0);
-                            let top_overlap = (platform.y + platform.height/2.0) - (state.player.y - state.player.height/2.0);
-                            //find the smallest overlap
-                            let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
-                            //apply the smallest overlap
-                            if smallest_overlap == left_overlap {
-                                state.player.data_num[0] = state.player.data_num[0].min(0.0);
-                                state.player.x = platform.x - platform.width/2.0 - state.player.width/2.0 + 1.0;
-                            } else if smallest_overlap == right_overlap {
-                                state.player.data_num[0] = state.player.data_num[0].max(0.0);
-                                state.player.x = platform.x + platform.width/2.0 + state.player.width/2.0 - 1.0;
-                            } else if smallest_overlap == top_overlap {
-                                state.player.data_num[1] = state.player.data_num[1].max(0.0);
-                                state.player.y = platform.y + platform.height/2.0 + state.player.height/2.0 - 1.0;
-                                state.player.data_bool[2] = true;
-                            } else if smallest_overlap == bottom_overlap {
-                                state.player.data_num[1] = state.player.data_num[1].min(0.0);
-                                state.player.y = platform.y - platform.height/2.0 - state.player.height/2.0 + 1.0;
-                            }
-                        }
+                    //apply bullet cooldown
+                    if state.player.data_num[2] > 0.0 {
+                        state.player.data_num[2] -= 0.05;
                     }
+                    state.player.data_bool[2] = false;
+                    //check for platform collisions
+                    collide_platforms(&mut state.player, &state.platforms.platforms);
                     //check for ground collisions
                     if state.player.y - state.player.height/2.0 < 0.0 {
                         state.player.data_bool[2] = true;
@@ -2221,141 +4379,32 @@ fn main() {
                     if clone.moves.sequence[clone.moves.step as usize].d {
                         clone.data_num[0] += clone.speed;
                     }
-                    if clone.moves.sequence[clone.moves.step as usize].w {
-                        if clone.data_bool[2] {
+                    if clone.moves.sequence[clone.moves.step as usize].w
+                        && clone.data_bool[2] {
                             clone.data_num[1] = clone.data_num[1].max(clone.jump);
                         }
-                    }
                     if clone.moves.sequence[clone.moves.step as usize].special {
-                        if !clone.data_bool[1] {
-                            if clone.data_num[2] <= 0.0 {
-                                clone.data_bool[1] = true;
-                                //shoot an explosive projectile
-                                //find all used IDs
-                                let mut used_ids = vec![];
-                                for bullet in state.player_bullets.bullets.clone() {
-                                    used_ids.push(bullet.id);
-                                }
-                                //use a new ID
-                                let mut new_id = 0;
-                                while used_ids.contains(&new_id) {
-                                    new_id += 1;
-                                }
-                                //add a new bullet
-                                let add = state.player_bullets.add.clone();
-                                (add)(Bullet {x: clone.x, y: clone.y, width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: clone.damage, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
-                                    let get = state.player_bullets.get.clone();
-                                    let mut bullet = (get)(id, state);
-                                    bullet.x += 10.0;
-                                    let remove = state.player_bullets.remove.clone();
-                                    (remove)(id, state);
-                                    let add = state.player_bullets.add.clone();
-                                    let overlap = state.platforms.platforms.iter().filter(|platform| {
-                                        bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
-                                    }).collect::<Vec<&Platform>>();
-                                    //check for enemy collisions
-                                    let mut hit_enemies = vec![];
-                                    for mut enemy in state.enemies.enemies.iter_mut() {
-                                        if bullet.x + bullet.width/2.0 > enemy.x - enemy.width/2.0 && bullet.x - bullet.width/2.0 < enemy.x + enemy.width/2.0 && bullet.y + bullet.height/2.0 > enemy.y - enemy.height/2.0 && bullet.y - bullet.height/2.0 < enemy.y + enemy.height/2.0 {
-                                            enemy.health -= bullet.damage;
-                                            hit_enemies.push(enemy.id);
-                                        }
-                                    }
-                                    if overlap.len() == 0 && hit_enemies.len() == 0 {
-                                        (add)(bullet, state);
-                                    } else {
-                                        //large explosion, damaging all enemies near the bullet
-                                        for mut enemy in state.enemies.enemies.iter_mut() {
-                                            if (bullet.x - enemy.x).abs() < 50.0 && (bullet.y - enemy.y).abs() < 50.0 {
-                                                enemy.health -= bullet.damage;
-                                            }
-                                        }
-                                    }
-                                }), id: new_id, image: 0}, state);
-                                clone.data_num[2] = 20.0;
-                            }
-                        } else {
-                            clone.data_bool[1] = false;
-                        }
-                    }
-<<<<<<< HEAD
-=======
-                    clone.data_bool[2] = false;
->>>>>>> 0bbb193309acffbfaebdc1ab3ddabe085f9e14c0
-                    if clone.data_num[2] > 0.0 {
-                        clone.data_num[2] -= 0.05;
-                    }
-                    //shooting
-                    if clone.data_bool[0] && clone.data_num[3] <= 0.0 {
-                        //find all used IDs
-                        let mut used_ids = vec![];
-                        for bullet in state.player_bullets.bullets.clone() {
-                            used_ids.push(bullet.id);
-                        }
-                        //use a new ID
-                        let mut new_id = 0;
-                        while used_ids.contains(&new_id) {
-                            new_id += 1;
+                        //stop moving, but slowly regenerate health
+                        clone.speed = 0.0;
+                        if clone.health < 500.0 {
+                            clone.health += clone.data_num[3];
                         }
-                        //add a new bullet
-                        let add = state.player_bullets.add.clone();
-                        (add)(Bullet {x: clone.x, y: clone.y, width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: clone.damage, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
-                            let get = state.player_bullets.get.clone();
-                            let mut bullet = (get)(id, state);
-                            bullet.x += 10.0;
-                            let remove = state.player_bullets.remove.clone();
-                            (remove)(id, state);
-                            let add = state.player_bullets.add.clone();
-                            let overlap = state.platforms.platforms.iter().filter(|platform| {
-                                bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
-                            }).collect::<Vec<&Platform>>();
-                            //check for enemy collisions
-                            let mut hit_enemies = vec![];
-                            for mut enemy in state.enemies.enemies.iter_mut() {
-                                if bullet.x + bullet.width/2.0 > enemy.x - enemy.width/2.0 && bullet.x - bullet.width/2.0 < enemy.x + enemy.width/2.0 && bullet.y + bullet.height/2.0 > enemy.y - enemy.height/2.0 && bullet.y - bullet.height/2.0 < enemy.y + enemy.height/2.0 {
-                                    enemy.health -= bullet.damage;
-                                    hit_enemies.push(enemy.id);
-                                }
-                            }
-                            if overlap.len() == 0 && hit_enemies.len() == 0 {
-                                (add)(bullet, state);
-                            }
-                        }), id: new_id, image: 0}, state);
-                        //apply bullet cooldown
-                        clone.data_num[3] = 1.0;
+                    } else {
+                        clone.speed = 0.5;
                     }
+                    //no shooting for the smasher, they are melee
                     //apply air resistance
                     clone.data_num[0] *= 0.9;
                     clone.data_num[1] *= 0.9;
                     //apply gravity
                     clone.data_num[1] -= 1.0;
-                    //check for platform collisions
-                    for platform in state.platforms.platforms.iter() {
-                        if (clone.x - platform.x).abs() < platform.width/2.0 + clone.width/2.0 && (clone.y - platform.y).abs() < platform.height/2.0 + clone.height/2.0 {
-                            //check which side of the platform is closest
-                            let left_overlap = (clone.x + clone.width/2.0) - (platform.x - platform.width/2.0);
-                            let right_overlap = (platform.x + platform.width/2.0) - (clone.x - clone.width/2.0);
-                            let bottom_overlap = (clone.y + clone.height/2.0) - (platform.y - platform.height/2.0);
-                            let top_overlap = (platform.y + platform.height/2.0) - (clone.y - clone.height/2.0);
-                            //find the smallest overlap
-                            let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
-                            //apply the smallest overlap
-                            if smallest_overlap == left_overlap {
-                                clone.data_num[0] = clone.data_num[0].min(0.0);
-                                clone.x = platform.x - platform.width/2.0 - clone.width/2.0 + 1.0;
-                            } else if smallest_overlap == right_overlap {
-                                clone.data_num[0] = clone.data_num[0].max(0.0);
-                                clone.x = platform.x + platform.width/2.0 + clone.width/2.0 - 1.0;
-                            } else if smallest_overlap == top_overlap {
-                                clone.data_num[1] = clone.data_num[1].max(0.0);
-                                clone.y = platform.y + platform.height/2.0 + clone.height/2.0 - 1.0;
-                                clone.data_bool[2] = true;
-                            } else if smallest_overlap == bottom_overlap {
-                                clone.data_num[1] = clone.data_num[1].min(0.0);
-                                clone.y = platform.y - platform.height/2.0 - clone.height/2.0 + 1.0;
-                            }
-                        }
+                    //apply bullet cooldown
+                    if clone.data_num[2] > 0.0 {
+                        clone.data_num[2] -= 0.05;
                     }
+                    clone.data_bool[2] = false;
+                    //check for platform collisions
+                    collide_platforms(&mut clone, &state.platforms.platforms);
                     //check for ground collisions
                     if clone.y - clone.height/2.0 < 0.0 {
                         clone.data_bool[2] = true;
@@ -2375,36 +4424,74 @@ fn main() {
                 reset: Rc::new(|player: &mut Player| {
                     player.x = 0.0;
                     player.y = 0.0;
-                    player.health = 70.0;
+                    player.health = 100.0;
                     player.data_bool = vec![true, false, false];
                     player.data_string = vec![];
-                    player.data_num = vec![0.0, 0.0, 0.0, 0.0];
+                    player.data_num = vec![0.0, 0.0, 0.0, 0.01];
                 }),
                 active: true,
-                image: 3,
+                image: 0,
+                units: vec![],
+                buffs: vec![],
+                invulnerable: 0.0,
+                flash: 0.0,
+                animation: "idle".to_string(),
+                animation_time: 0.0,
+                tilt: 0.0,
+                max_health: 0.0,
+                revived: false,
+                ability: Some(ability("shockwave")),
+                weapon: None,
+                loop_made: 0,
+                upgrades: vec![
+                    Upgrade {
+                        name: "armour".to_string(),
+                        level: 0,
+                        max_level: 5,
+                        cost: 20,
+                        requires: "".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            player.health += 50.0*level as f64;
+                        }),
+                    },
+                    Upgrade {
+                        name: "regeneration".to_string(),
+                        level: 0,
+                        max_level: 4,
+                        cost: 30,
+                        requires: "armour".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            player.data_num[3] = 0.01*(1.0 + level as f64);
+                        }),
+                    },
+                    Upgrade {
+                        name: "heavy frame".to_string(),
+                        level: 0,
+                        max_level: 3,
+                        cost: 30,
+                        requires: "regeneration".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            player.jump += 5.0*level as f64;
+                        }),
+                    },
+                ],
+                vehicle: 1,
+                artifacts: vec![],
             },
-            //overall quite weak, but there can be multiple clones of it, so it just looks weak here
+            //high damage arcing projectiles, low health, slow, shoots explosive projectile on ability
             Player {
-                damage: 0.7,
-<<<<<<< HEAD
-<<<<<<< HEAD
-                id: "Swarm".to_string(),
-=======
-                id: 1.to_string(),
->>>>>>> 0bbb193309acffbfaebdc1ab3ddabe085f9e14c0
-=======
-                id: "Swarm".to_string(),
->>>>>>> aee92832657bf1e2267e9e48ba9d17fc744522d7
+                damage: 100.0,
+                id: "Cannoneer".to_string(),
                 x: 0.0,
                 y: 0.0,
                 width: 50.0,
                 height: 60.0,
-                health: 50.0,
-                speed: 0.8,
-                jump: 35.0,
+                health: 70.0,
+                speed: 0.5,
+                jump: 50.0,
                 data_bool: vec![true, false, false],
                 data_string: vec![],
-                data_num: vec![0.0, 0.0, 0.0, 1.0],
+                data_num: vec![0.0, 0.0, 0.0, 0.0, 300.0],
                 moves: KeySequence {sequence: vec![], step: 0, length: 0},
                 apply_inputs: Rc::new(|state: &mut Game| {
                     if state.player.moves.sequence[state.player.moves.step as usize].a {
@@ -2416,95 +4503,26 @@ fn main() {
                     if state.player.moves.sequence[state.player.moves.step as usize].d {
                         state.player.data_num[0] += state.player.speed;
                     }
-                    if state.player.moves.sequence[state.player.moves.step as usize].w {
-                        if state.player.data_bool[2] {
+                    if state.player.moves.sequence[state.player.moves.step as usize].w
+                        && state.player.data_bool[2] {
                             state.player.data_num[1] = state.player.data_num[1].max(state.player.jump);
                         }
-                    }
-                    if state.player.moves.sequence[state.player.moves.step as usize].special {
-                        if !state.player.data_bool[1] {
-                            state.player.data_bool[1] = true;
-                            //invert the shooting boolean
-                            state.player.data_bool[0] = !state.player.data_bool[0];
-                        }
-                    } else {
-                        state.player.data_bool[1] = false;
-                    }
-                    //shooting
-                    if state.player.data_bool[0] && state.player.data_num[2] <= 0.0 {
-                        state.player.data_num[2] = 1.0;
-                        //find all used IDs
-                        let mut used_ids = vec![];
-                        for bullet in state.player_bullets.bullets.clone() {
-                            used_ids.push(bullet.id);
-                        }
-                        //use a new ID
-                        let mut new_id = 0;
-                        while used_ids.contains(&new_id) {
-                            new_id += 1;
+                    //the cannon fires whenever special is held and it has reloaded
+                    let mut weapon = state.player.weapon.take();
+                    if let Some(weapon) = weapon.as_mut() {
+                        if weapon_ready(weapon, state.player.moves.sequence[state.player.moves.step as usize].special) {
+                            fire_weapon(weapon, state.player.x, state.player.y, state.player.moves.sequence[state.player.moves.step as usize].aim, state.player.damage, "player", state);
                         }
-                        //add a new bullet
-                        let add = state.player_bullets.add.clone();
-                        (add)(Bullet {x: state.player.x, y: state.player.y, width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: state.player.damage, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
-                            let get = state.player_bullets.get.clone();
-                            let mut bullet = (get)(id, state);
-                            bullet.x += 10.0;
-                            let remove = state.player_bullets.remove.clone();
-                            (remove)(id, state);
-                            let add = state.player_bullets.add.clone();
-                            let overlap = state.platforms.platforms.iter().filter(|platform| {
-                                bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
-                            }).collect::<Vec<&Platform>>();
-                            //check for enemy collisions
-                            let mut hit_enemies = vec![];
-                            for mut enemy in state.enemies.enemies.iter_mut() {
-                                if bullet.x + bullet.width/2.0 > enemy.x - enemy.width/2.0 && bullet.x - bullet.width/2.0 < enemy.x + enemy.width/2.0 && bullet.y + bullet.height/2.0 > enemy.y - enemy.height/2.0 && bullet.y - bullet.height/2.0 < enemy.y + enemy.height/2.0 {
-                                    enemy.health -= bullet.damage;
-                                    hit_enemies.push(enemy.id);
-                                }
-                            }
-                            if overlap.len() == 0 && hit_enemies.len() == 0 {
-                                (add)(bullet, state);
-                            }
-                        }), id: new_id, image: 0}, state);
                     }
+                    state.player.weapon = weapon;
+                    state.player.data_bool[2] = false;
                     //apply air resistance
                     state.player.data_num[0] *= 0.9;
                     state.player.data_num[1] *= 0.9;
                     //apply gravity
                     state.player.data_num[1] -= 1.0;
-                    //apply bullet cooldown
-                    if state.player.data_num[2] > 0.0 {
-                        state.player.data_num[2] -= 0.05;
-                    }
-                    state.player.data_bool[2] = false;
                     //check for platform collisions
-                    for platform in state.platforms.platforms.iter() {
-                        if (state.player.x - platform.x).abs() < platform.width/2.0 + state.player.width/2.0 && (state.player.y - platform.y).abs() < platform.height/2.0 + state.player.height/2.0 {
-                            //check which side of the platform is closest
-                            let left_overlap = (state.player.x + state.player.width/2.0) - (platform.x - platform.width/2.0);
-                            let right_overlap = (platform.x + platform.width/2.0) - (state.player.x - state.player.width/2.0);
-                            let bottom_overlap = (state.player.y + state.player.height/2.0) - (platform.y - platform.height/2.0);
-                            let top_overlap = (platform.y + platform.height/2.0) - (state.player.y - state.player.height/2.0);
-                            //find the smallest overlap
-                            let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
-                            //apply the smallest overlap
-                            if smallest_overlap == left_overlap {
-                                state.player.data_num[0] = state.player.data_num[0].min(0.0);
-                                state.player.x = platform.x - platform.width/2.0 - state.player.width/2.0 + 1.0;
-                            } else if smallest_overlap == right_overlap {
-                                state.player.data_num[0] = state.player.data_num[0].max(0.0);
-                                state.player.x = platform.x + platform.width/2.0 + state.player.width/2.0 - 1.0;
-                            } else if smallest_overlap == top_overlap {
-                                state.player.data_num[1] = state.player.data_num[1].max(0.0);
-                                state.player.y = platform.y + platform.height/2.0 + state.player.height/2.0 - 1.0;
-                                state.player.data_bool[2] = true;
-                            } else if smallest_overlap == bottom_overlap {
-                                state.player.data_num[1] = state.player.data_num[1].min(0.0);
-                                state.player.y = platform.y - platform.height/2.0 - state.player.height/2.0 + 1.0;
-                            }
-                        }
-                    }
+                    collide_platforms(&mut state.player, &state.platforms.platforms);
                     //check for ground collisions
                     if state.player.y - state.player.height/2.0 < 0.0 {
                         state.player.data_bool[2] = true;
@@ -2531,95 +4549,26 @@ fn main() {
                     if clone.moves.sequence[clone.moves.step as usize].d {
                         clone.data_num[0] += clone.speed;
                     }
-                    if clone.moves.sequence[clone.moves.step as usize].w {
-                        if clone.data_bool[2] {
+                    if clone.moves.sequence[clone.moves.step as usize].w
+                        && clone.data_bool[2] {
                             clone.data_num[1] = clone.data_num[1].max(clone.jump);
                         }
-                    }
-                    if clone.moves.sequence[clone.moves.step as usize].special {
-                        if !clone.data_bool[1] {
-                            clone.data_bool[1] = true;
-                            //invert the shooting boolean
-                            clone.data_bool[0] = !clone.data_bool[0];
-                        }
-                    } else {
-                        clone.data_bool[1] = false;
-                    }
-                    //shooting
-                    if clone.data_bool[0] && clone.data_num[2] <= 0.0 {
-                        clone.data_num[2] = 1.8;
-                        //find all used IDs
-                        let mut used_ids = vec![];
-                        for bullet in state.player_bullets.bullets.clone() {
-                            used_ids.push(bullet.id);
-                        }
-                        //use a new ID
-                        let mut new_id = 0;
-                        while used_ids.contains(&new_id) {
-                            new_id += 1;
+                    //the cannon fires whenever special is held and it has reloaded
+                    let mut weapon = clone.weapon.take();
+                    if let Some(weapon) = weapon.as_mut() {
+                        if weapon_ready(weapon, clone.moves.sequence[clone.moves.step as usize].special) {
+                            fire_weapon(weapon, clone.x, clone.y, clone.moves.sequence[clone.moves.step as usize].aim, clone.damage, "clone", state);
                         }
-                        //add a new bullet
-                        let add = state.player_bullets.add.clone();
-                        (add)(Bullet {x: clone.x, y: clone.y, width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: clone.damage, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
-                            let get = state.player_bullets.get.clone();
-                            let mut bullet = (get)(id, state);
-                            bullet.x += 10.0;
-                            let remove = state.player_bullets.remove.clone();
-                            (remove)(id, state);
-                            let add = state.player_bullets.add.clone();
-                            let overlap = state.platforms.platforms.iter().filter(|platform| {
-                                bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
-                            }).collect::<Vec<&Platform>>();
-                            //check for enemy collisions
-                            let mut hit_enemies = vec![];
-                            for mut enemy in state.enemies.enemies.iter_mut() {
-                                if bullet.x + bullet.width/2.0 > enemy.x - enemy.width/2.0 && bullet.x - bullet.width/2.0 < enemy.x + enemy.width/2.0 && bullet.y + bullet.height/2.0 > enemy.y - enemy.height/2.0 && bullet.y - bullet.height/2.0 < enemy.y + enemy.height/2.0 {
-                                    enemy.health -= bullet.damage;
-                                    hit_enemies.push(enemy.id);
-                                }
-                            }
-                            if overlap.len() == 0 && hit_enemies.len() == 0 {
-                                (add)(bullet, state);
-                            }
-                        }), id: new_id, image: 0}, state);
                     }
+                    clone.weapon = weapon;
+                    clone.data_bool[2] = false;
                     //apply air resistance
                     clone.data_num[0] *= 0.9;
                     clone.data_num[1] *= 0.9;
                     //apply gravity
                     clone.data_num[1] -= 1.0;
-                    //apply bullet cooldown
-                    if clone.data_num[2] > 0.0 {
-                        clone.data_num[2] -= 0.05;
-                    }
-                    clone.data_bool[2] = false;
                     //check for platform collisions
-                    for platform in state.platforms.platforms.iter() {
-                        if (clone.x - platform.x).abs() < platform.width/2.0 + clone.width/2.0 && (clone.y - platform.y).abs() < platform.height/2.0 + clone.height/2.0 {
-                            //check which side of the platform is closest
-                            let left_overlap = (clone.x + clone.width/2.0) - (platform.x - platform.width/2.0);
-                            let right_overlap = (platform.x + platform.width/2.0) - (clone.x - clone.width/2.0);
-                            let bottom_overlap = (clone.y + clone.height/2.0) - (platform.y - platform.height/2.0);
-                            let top_overlap = (platform.y + platform.height/2.0) - (clone.y - clone.height/2.0);
-                            //find the smallest overlap
-                            let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
-                            //apply the smallest overlap
-                            if smallest_overlap == left_overlap {
-                                clone.data_num[0] = clone.data_num[0].min(0.0);
-                                clone.x = platform.x - platform.width/2.0 - clone.width/2.0 + 1.0;
-                            } else if smallest_overlap == right_overlap {
-                                clone.data_num[0] = clone.data_num[0].max(0.0);
-                                clone.x = platform.x + platform.width/2.0 + clone.width/2.0 - 1.0;
-                            } else if smallest_overlap == top_overlap {
-                                clone.data_num[1] = clone.data_num[1].max(0.0);
-                                clone.y = platform.y + platform.height/2.0 + clone.height/2.0 - 1.0;
-                                clone.data_bool[2] = true;
-                            } else if smallest_overlap == bottom_overlap {
-                                clone.data_num[1] = clone.data_num[1].min(0.0);
-                                clone.y = platform.y - platform.height/2.0 - clone.height/2.0 + 1.0;
-                            }
-                        }
-                    }
+                    collide_platforms(&mut clone, &state.platforms.platforms);
                     //check for ground collisions
                     if clone.y - clone.height/2.0 < 0.0 {
                         clone.data_bool[2] = true;
@@ -2639,46 +4588,78 @@ fn main() {
                 reset: Rc::new(|player: &mut Player| {
                     player.x = 0.0;
                     player.y = 0.0;
-                    player.health = 50.0;
+                    player.health = 70.0;
                     player.data_bool = vec![true, false, false];
                     player.data_string = vec![];
-                    player.data_num = vec![0.0, 0.0, 0.0];
+                    player.data_num = vec![0.0, 0.0, 0.0, 0.0, 300.0];
                 }),
                 active: true,
-                image: 4,
-<<<<<<< HEAD
-=======
+                image: 3,
+                units: vec![],
+                buffs: vec![],
+                invulnerable: 0.0,
+                flash: 0.0,
+                animation: "idle".to_string(),
+                animation_time: 0.0,
+                tilt: 0.0,
+                max_health: 0.0,
+                revived: false,
+                ability: Some(ability("barrage")),
+                weapon: Some(weapon("cannon")),
+                loop_made: 0,
+                upgrades: vec![
+                    Upgrade {
+                        name: "armour".to_string(),
+                        level: 0,
+                        max_level: 5,
+                        cost: 20,
+                        requires: "".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            player.health += 10.0*level as f64;
+                        }),
+                    },
+                    Upgrade {
+                        name: "weapons".to_string(),
+                        level: 0,
+                        max_level: 5,
+                        cost: 25,
+                        requires: "armour".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            player.damage *= 1.0 + 0.2*level as f64;
+                        }),
+                    },
+                    Upgrade {
+                        name: "bigger blast".to_string(),
+                        level: 0,
+                        max_level: 4,
+                        cost: 40,
+                        requires: "weapons".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            if let Some(weapon) = player.weapon.as_mut() {
+                                weapon.blast_radius += 75.0*level as f64;
+                            }
+                        }),
+                    },
+                ],
+                vehicle: 2,
+                artifacts: vec![],
             },
-            //high agility
+            //overall quite weak, but there can be multiple clones of it, so it just looks weak here
             Player {
-                damage: 0.8,
-                id: "mcQueen".to_string(),
+                damage: 0.7,
+                id: "Swarm".to_string(),
                 x: 0.0,
                 y: 0.0,
                 width: 50.0,
                 height: 60.0,
-                health: 65.0,
-                speed: 2.0,
-                jump: 70.0,
+                health: 50.0,
+                speed: 0.8,
+                jump: 35.0,
                 data_bool: vec![true, false, false],
                 data_string: vec![],
-                data_num: vec![0.0, 0.0, 0.0],
+                data_num: vec![0.0, 0.0, 0.0, 3.0],
                 moves: KeySequence {sequence: vec![], step: 0, length: 0},
                 apply_inputs: Rc::new(|state: &mut Game| {
-                    if state.player.moves.sequence[state.player.moves.step as usize].a {
-                        state.player.data_num[0] -= state.player.speed;
-                    }
-                    if state.player.moves.sequence[state.player.moves.step as usize].s {
-                        state.player.data_num[1] = state.player.data_num[1].min(0.0);
-                    }
-                    if state.player.moves.sequence[state.player.moves.step as usize].d {
-                        state.player.data_num[0] += state.player.speed;
-                    }
-                    if state.player.moves.sequence[state.player.moves.step as usize].w {
-                        if state.player.data_bool[2] {
-                            state.player.data_num[1] = state.player.data_num[1].max(state.player.jump);
-                        }
-                    }
                     if state.player.moves.sequence[state.player.moves.step as usize].special {
                         if !state.player.data_bool[1] {
                             state.player.data_bool[1] = true;
@@ -2689,89 +4670,19 @@ fn main() {
                         state.player.data_bool[1] = false;
                     }
                     //shooting
-                    if state.player.data_bool[0] && state.player.data_num[2] <= 0.0 {
-                        state.player.data_num[2] = 1.0;
-                        //find all used IDs
-                        let mut used_ids = vec![];
-                        for bullet in state.player_bullets.bullets.clone() {
-                            used_ids.push(bullet.id);
-                        }
-                        //use a new ID
-                        let mut new_id = 0;
-                        while used_ids.contains(&new_id) {
-                            new_id += 1;
-                        }
-                        //add a new bullet
-                        let add = state.player_bullets.add.clone();
-                        (add)(Bullet {x: state.player.x, y: state.player.y, width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: state.player.damage, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
-                            let get = state.player_bullets.get.clone();
-                            let mut bullet = (get)(id, state);
-                            bullet.x += 10.0;
-                            let remove = state.player_bullets.remove.clone();
-                            (remove)(id, state);
-                            let add = state.player_bullets.add.clone();
-                            let overlap = state.platforms.platforms.iter().filter(|platform| {
-                                bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
-                            }).collect::<Vec<&Platform>>();
-                            //check for enemy collisions
-                            let mut hit_enemies = vec![];
-                            for mut enemy in state.enemies.enemies.iter_mut() {
-                                if bullet.x + bullet.width/2.0 > enemy.x - enemy.width/2.0 && bullet.x - bullet.width/2.0 < enemy.x + enemy.width/2.0 && bullet.y + bullet.height/2.0 > enemy.y - enemy.height/2.0 && bullet.y - bullet.height/2.0 < enemy.y + enemy.height/2.0 {
-                                    enemy.health -= bullet.damage;
-                                    hit_enemies.push(enemy.id);
-                                }
-                            }
-                            if overlap.len() == 0 && hit_enemies.len() == 0 {
-                                (add)(bullet, state);
-                            }
-                        }), id: new_id, image: 0}, state);
-                    }
-                    //apply air resistance
-                    state.player.data_num[0] *= 0.9;
-                    state.player.data_num[1] *= 0.9;
-                    //apply gravity
-                    state.player.data_num[1] -= 1.0;
-                    //apply bullet cooldown
-                    if state.player.data_num[2] > 0.0 {
-                        state.player.data_num[2] -= 0.05;
-                    }
-                    state.player.data_bool[2] = false;
-                    //check for platform collisions
-                    for platform in state.platforms.platforms.iter() {
-                        if (state.player.x - platform.x).abs() < platform.width/2.0 + state.player.width/2.0 && (state.player.y - platform.y).abs() < platform.height/2.0 + state.player.height/2.0 {
-                            //check which side of the platform is closest
-                            let left_overlap = (state.player.x + state.player.width/2.0) - (platform.x - platform.width/2.0);
-                            let right_overlap = (platform.x + platform.width/2.0) - (state.player.x - state.player.width/2.0);
-                            let bottom_overlap = (state.player.y + state.player.height/2.0) - (platform.y - platform.height/2.0);
-                            let top_overlap = (platform.y + platform.height/2.0) - (state.player.y - state.player.height/2.0);
-                            //find the smallest overlap
-                            let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
-                            //apply the smallest overlap
-                            if smallest_overlap == left_overlap {
-                                state.player.data_num[0] = state.player.data_num[0].min(0.0);
-                                state.player.x = platform.x - platform.width/2.0 - state.player.width/2.0 + 1.0;
-                            } else if smallest_overlap == right_overlap {
-                                state.player.data_num[0] = state.player.data_num[0].max(0.0);
-                                state.player.x = platform.x + platform.width/2.0 + state.player.width/2.0 - 1.0;
-                            } else if smallest_overlap == top_overlap {
-                                state.player.data_num[1] = state.player.data_num[1].max(0.0);
-                                state.player.y = platform.y + platform.height/2.0 + state.player.height/2.0 - 1.0;
-                                state.player.data_bool[2] = true;
-                            } else if smallest_overlap == bottom_overlap {
-                                state.player.data_num[1] = state.player.data_num[1].min(0.0);
-                                state.player.y = platform.y - platform.height/2.0 - state.player.height/2.0 + 1.0;
+                    //move the units
+                    let inputs = state.player.moves.sequence[state.player.moves.step as usize].clone();
+                    update_units(&mut state.player, &inputs, &state.platforms.platforms);
+                    let mut weapon = state.player.weapon.take();
+                    if let Some(weapon) = weapon.as_mut() {
+                        if weapon_ready(weapon, state.player.data_bool[0]) {
+                            //every unit in the swarm fires its own shot
+                            for unit in state.player.units.clone() {
+                                fire_weapon(weapon, unit.x, unit.y, state.player.moves.sequence[state.player.moves.step as usize].aim, state.player.damage, "player", state);
                             }
                         }
                     }
-                    //check for ground collisions
-                    if state.player.y - state.player.height/2.0 < 0.0 {
-                        state.player.data_bool[2] = true;
-                        state.player.data_num[1] = state.player.data_num[1].max(0.0);
-                        state.player.y = state.player.height/2.0 - 1.0;
-                    }
-                    //move the player
-                    state.player.x += state.player.data_num[0];
-                    state.player.y += state.player.data_num[1];
+                    state.player.weapon = weapon;
                     //update the step
                     if state.player.moves.step < state.player.moves.length-1 {
                         state.player.moves.step += 1;
@@ -2780,113 +4691,29 @@ fn main() {
                 apply_inputs_as_clone: Rc::new(|state: &mut Game, id: String| {
                     let get = state.clones.get.clone();
                     let mut clone = (get)(id, state);
-                    if clone.moves.sequence[clone.moves.step as usize].a {
-                        clone.data_num[0] -= clone.speed;
-                    }
-                    if clone.moves.sequence[clone.moves.step as usize].s {
-                        clone.data_num[1] = clone.data_num[1].min(0.0);
-                    }
-                    if clone.moves.sequence[clone.moves.step as usize].d {
-                        clone.data_num[0] += clone.speed;
-                    }
-                    if clone.moves.sequence[clone.moves.step as usize].w {
-                        if clone.data_bool[2] {
-                            clone.data_num[1] = clone.data_num[1].max(clone.jump);
-                        }
-                    }
                     if clone.moves.sequence[clone.moves.step as usize].special {
-                        if !clone.data_bool[1] {
-                            clone.data_bool[1] = true;
-                            //invert the shooting boolean
-                            clone.data_bool[0] = !clone.data_bool[0];
-                        }
-                    } else {
-                        clone.data_bool[1] = false;
-                    }
-                    //shooting
-                    if clone.data_bool[0] && clone.data_num[2] <= 0.0 {
-                        clone.data_num[2] = 1.0;
-                        //find all used IDs
-                        let mut used_ids = vec![];
-                        for bullet in state.player_bullets.bullets.clone() {
-                            used_ids.push(bullet.id);
-                        }
-                        //use a new ID
-                        let mut new_id = 0;
-                        while used_ids.contains(&new_id) {
-                            new_id += 1;
-                        }
-                        //add a new bullet
-                        let add = state.player_bullets.add.clone();
-                        (add)(Bullet {x: clone.x, y: clone.y, width: 10.0, height: 10.0, speed: 10.0, direction: 0.0, damage: clone.damage, data_bool: vec![], data_string: vec![], data_num: vec![], update: Rc::new(|id: u32, mut state: &mut Game| {
-                            let get = state.player_bullets.get.clone();
-                            let mut bullet = (get)(id, state);
-                            bullet.x += 10.0;
-                            let remove = state.player_bullets.remove.clone();
-                            (remove)(id, state);
-                            let add = state.player_bullets.add.clone();
-                            let overlap = state.platforms.platforms.iter().filter(|platform| {
-                                bullet.x + bullet.width/2.0 > platform.x - platform.width/2.0 && bullet.x - bullet.width/2.0 < platform.x + platform.width/2.0 && bullet.y + bullet.height/2.0 > platform.y - platform.height/2.0 && bullet.y - bullet.height/2.0 < platform.y + platform.height/2.0
-                            }).collect::<Vec<&Platform>>();
-                            //check for enemy collisions
-                            let mut hit_enemies = vec![];
-                            for mut enemy in state.enemies.enemies.iter_mut() {
-                                if bullet.x + bullet.width/2.0 > enemy.x - enemy.width/2.0 && bullet.x - bullet.width/2.0 < enemy.x + enemy.width/2.0 && bullet.y + bullet.height/2.0 > enemy.y - enemy.height/2.0 && bullet.y - bullet.height/2.0 < enemy.y + enemy.height/2.0 {
-                                    enemy.health -= bullet.damage;
-                                    hit_enemies.push(enemy.id);
-                                }
-                            }
-                            if overlap.len() == 0 && hit_enemies.len() == 0 {
-                                (add)(bullet, state);
-                            }
-                        }), id: new_id, image: 0}, state);
-                    }
-                    //apply air resistance
-                    clone.data_num[0] *= 0.9;
-                    clone.data_num[1] *= 0.9;
-                    //apply gravity
-                    clone.data_num[1] -= 1.0;
-                    //apply bullet cooldown
-                    if clone.data_num[2] > 0.0 {
-                        clone.data_num[2] -= 0.05;
+                        if !clone.data_bool[1] {
+                            clone.data_bool[1] = true;
+                            //invert the shooting boolean
+                            clone.data_bool[0] = !clone.data_bool[0];
+                        }
+                    } else {
+                        clone.data_bool[1] = false;
                     }
-                    clone.data_bool[2] = false;
-                    //check for platform collisions
-                    for platform in state.platforms.platforms.iter() {
-                        if (clone.x - platform.x).abs() < platform.width/2.0 + clone.width/2.0 && (clone.y - platform.y).abs() < platform.height/2.0 + clone.height/2.0 {
-                            //check which side of the platform is closest
-                            let left_overlap = (clone.x + clone.width/2.0) - (platform.x - platform.width/2.0);
-                            let right_overlap = (platform.x + platform.width/2.0) - (clone.x - clone.width/2.0);
-                            let bottom_overlap = (clone.y + clone.height/2.0) - (platform.y - platform.height/2.0);
-                            let top_overlap = (platform.y + platform.height/2.0) - (clone.y - clone.height/2.0);
-                            //find the smallest overlap
-                            let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
-                            //apply the smallest overlap
-                            if smallest_overlap == left_overlap {
-                                clone.data_num[0] = clone.data_num[0].min(0.0);
-                                clone.x = platform.x - platform.width/2.0 - clone.width/2.0 + 1.0;
-                            } else if smallest_overlap == right_overlap {
-                                clone.data_num[0] = clone.data_num[0].max(0.0);
-                                clone.x = platform.x + platform.width/2.0 + clone.width/2.0 - 1.0;
-                            } else if smallest_overlap == top_overlap {
-                                clone.data_num[1] = clone.data_num[1].max(0.0);
-                                clone.y = platform.y + platform.height/2.0 + clone.height/2.0 - 1.0;
-                                clone.data_bool[2] = true;
-                            } else if smallest_overlap == bottom_overlap {
-                                clone.data_num[1] = clone.data_num[1].min(0.0);
-                                clone.y = platform.y - platform.height/2.0 - clone.height/2.0 + 1.0;
+                    //shooting
+                    //move the units
+                    let inputs = clone.moves.sequence[clone.moves.step as usize].clone();
+                    update_units(&mut clone, &inputs, &state.platforms.platforms);
+                    let mut weapon = clone.weapon.take();
+                    if let Some(weapon) = weapon.as_mut() {
+                        if weapon_ready(weapon, clone.data_bool[0]) {
+                            //every unit in the swarm fires its own shot
+                            for unit in clone.units.clone() {
+                                fire_weapon(weapon, unit.x, unit.y, clone.moves.sequence[clone.moves.step as usize].aim, clone.damage, "clone", state);
                             }
                         }
                     }
-                    //check for ground collisions
-                    if clone.y - clone.height/2.0 < 0.0 {
-                        clone.data_bool[2] = true;
-                        clone.data_num[1] = clone.data_num[1].max(0.0);
-                        clone.y = clone.height/2.0 - 1.0;
-                    }
-                    //move the player
-                    clone.x += clone.data_num[0];
-                    clone.y += clone.data_num[1];
+                    clone.weapon = weapon;
                     //update the step
                     if clone.moves.step < clone.moves.length-1 {
                         clone.moves.step += 1;
@@ -2897,25 +4724,72 @@ fn main() {
                 reset: Rc::new(|player: &mut Player| {
                     player.x = 0.0;
                     player.y = 0.0;
-                    player.health = 65.0;
+                    player.health = 50.0;
                     player.data_bool = vec![true, false, false];
                     player.data_string = vec![];
-                    player.data_num = vec![0.0, 0.0, 0.0];
+                    player.data_num = vec![0.0, 0.0, 0.0, 3.0];
+                    player.units = vec![];
                 }),
                 active: true,
-                image: 5,
+                image: 4,
+                units: vec![],
+                buffs: vec![],
+                invulnerable: 0.0,
+                flash: 0.0,
+                animation: "idle".to_string(),
+                animation_time: 0.0,
+                tilt: 0.0,
+                max_health: 0.0,
+                revived: false,
+                ability: Some(ability("rally")),
+                weapon: Some(weapon("blaster")),
+                loop_made: 0,
+                upgrades: vec![
+                    Upgrade {
+                        name: "armour".to_string(),
+                        level: 0,
+                        max_level: 5,
+                        cost: 20,
+                        requires: "".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            player.health += 8.0*level as f64;
+                        }),
+                    },
+                    Upgrade {
+                        name: "weapons".to_string(),
+                        level: 0,
+                        max_level: 5,
+                        cost: 25,
+                        requires: "armour".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            player.damage *= 1.0 + 0.2*level as f64;
+                        }),
+                    },
+                    Upgrade {
+                        name: "bigger swarm".to_string(),
+                        level: 0,
+                        max_level: 4,
+                        cost: 50,
+                        requires: "armour".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            player.data_num[3] += level as f64;
+                        }),
+                    },
+                ],
+                vehicle: 3,
+                artifacts: vec![],
             },
-            //mid health, doesn't shoot, generates kills over time
+            //high agility
             Player {
-                damage: 1.0,
-                id: "Generator".to_string(),
+                damage: 0.8,
+                id: "mcQueen".to_string(),
                 x: 0.0,
                 y: 0.0,
                 width: 50.0,
                 height: 60.0,
-                health: 70.0,
-                speed: 1.0,
-                jump: 50.0,
+                health: 65.0,
+                speed: 2.0,
+                jump: 70.0,
                 data_bool: vec![true, false, false],
                 data_string: vec![],
                 data_num: vec![0.0, 0.0, 0.0],
@@ -2930,62 +4804,35 @@ fn main() {
                     if state.player.moves.sequence[state.player.moves.step as usize].d {
                         state.player.data_num[0] += state.player.speed;
                     }
-                    if state.player.moves.sequence[state.player.moves.step as usize].w {
-                        if state.player.data_bool[2] {
+                    if state.player.moves.sequence[state.player.moves.step as usize].w
+                        && state.player.data_bool[2] {
                             state.player.data_num[1] = state.player.data_num[1].max(state.player.jump);
                         }
-                    }
                     if state.player.moves.sequence[state.player.moves.step as usize].special {
                         if !state.player.data_bool[1] {
                             state.player.data_bool[1] = true;
-                            //invert the speed generation boolean
+                            //invert the shooting boolean
                             state.player.data_bool[0] = !state.player.data_bool[0];
-                            if state.player.data_bool[0] {
-                                state.player.speed = 0.0;
-                            } else {
-                                state.player.speed = 1.0;
-                            }
                         }
                     } else {
                         state.player.data_bool[1] = false;
                     }
+                    //shooting
+                    let mut weapon = state.player.weapon.take();
+                    if let Some(weapon) = weapon.as_mut() {
+                        if weapon_ready(weapon, state.player.data_bool[0]) {
+                            fire_weapon(weapon, state.player.x, state.player.y, state.player.moves.sequence[state.player.moves.step as usize].aim, state.player.damage, "player", state);
+                        }
+                    }
+                    state.player.weapon = weapon;
                     //apply air resistance
                     state.player.data_num[0] *= 0.9;
                     state.player.data_num[1] *= 0.9;
                     //apply gravity
                     state.player.data_num[1] -= 1.0;
-                    //apply bullet cooldown
-                    if state.player.data_num[2] > 0.0 {
-                        state.player.data_num[2] -= 0.05;
-                    }
                     state.player.data_bool[2] = false;
                     //check for platform collisions
-                    for platform in state.platforms.platforms.iter() {
-                        if (state.player.x - platform.x).abs() < platform.width/2.0 + state.player.width/2.0 && (state.player.y - platform.y).abs() < platform.height/2.0 + state.player.height/2.0 {
-                            //check which side of the platform is closest
-                            let left_overlap = (state.player.x + state.player.width/2.0) - (platform.x - platform.width/2.0);
-                            let right_overlap = (platform.x + platform.width/2.0) - (state.player.x - state.player.width/2.0);
-                            let bottom_overlap = (state.player.y + state.player.height/2.0) - (platform.y - platform.height/2.0);
-                            let top_overlap = (platform.y + platform.height/2.0) - (state.player.y - state.player.height/2.0);
-                            //find the smallest overlap
-                            let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
-                            //apply the smallest overlap
-                            if smallest_overlap == left_overlap {
-                                state.player.data_num[0] = state.player.data_num[0].min(0.0);
-                                state.player.x = platform.x - platform.width/2.0 - state.player.width/2.0 + 1.0;
-                            } else if smallest_overlap == right_overlap {
-                                state.player.data_num[0] = state.player.data_num[0].max(0.0);
-                                state.player.x = platform.x + platform.width/2.0 + state.player.width/2.0 - 1.0;
-                            } else if smallest_overlap == top_overlap {
-                                state.player.data_num[1] = state.player.data_num[1].max(0.0);
-                                state.player.y = platform.y + platform.height/2.0 + state.player.height/2.0 - 1.0;
-                                state.player.data_bool[2] = true;
-                            } else if smallest_overlap == bottom_overlap {
-                                state.player.data_num[1] = state.player.data_num[1].min(0.0);
-                                state.player.y = platform.y - platform.height/2.0 - state.player.height/2.0 + 1.0;
-                            }
-                        }
-                    }
+                    collide_platforms(&mut state.player, &state.platforms.platforms);
                     //check for ground collisions
                     if state.player.y - state.player.height/2.0 < 0.0 {
                         state.player.data_bool[2] = true;
@@ -3012,62 +4859,35 @@ fn main() {
                     if clone.moves.sequence[clone.moves.step as usize].d {
                         clone.data_num[0] += clone.speed;
                     }
-                    if clone.moves.sequence[clone.moves.step as usize].w {
-                        if clone.data_bool[2] {
+                    if clone.moves.sequence[clone.moves.step as usize].w
+                        && clone.data_bool[2] {
                             clone.data_num[1] = clone.data_num[1].max(clone.jump);
                         }
-                    }
                     if clone.moves.sequence[clone.moves.step as usize].special {
                         if !clone.data_bool[1] {
                             clone.data_bool[1] = true;
-                            //invert the speed generation boolean
+                            //invert the shooting boolean
                             clone.data_bool[0] = !clone.data_bool[0];
-                            if clone.data_bool[0] {
-                                clone.speed = 0.0;
-                            } else {
-                                clone.speed = 1.0;
-                            }
                         }
                     } else {
                         clone.data_bool[1] = false;
                     }
+                    //shooting
+                    let mut weapon = clone.weapon.take();
+                    if let Some(weapon) = weapon.as_mut() {
+                        if weapon_ready(weapon, clone.data_bool[0]) {
+                            fire_weapon(weapon, clone.x, clone.y, clone.moves.sequence[clone.moves.step as usize].aim, clone.damage, "clone", state);
+                        }
+                    }
+                    clone.weapon = weapon;
                     //apply air resistance
                     clone.data_num[0] *= 0.9;
                     clone.data_num[1] *= 0.9;
                     //apply gravity
                     clone.data_num[1] -= 1.0;
-                    //apply bullet cooldown
-                    if clone.data_num[2] > 0.0 {
-                        clone.data_num[2] -= 0.05;
-                    }
                     clone.data_bool[2] = false;
                     //check for platform collisions
-                    for platform in state.platforms.platforms.iter() {
-                        if (clone.x - platform.x).abs() < platform.width/2.0 + clone.width/2.0 && (clone.y - platform.y).abs() < platform.height/2.0 + clone.height/2.0 {
-                            //check which side of the platform is closest
-                            let left_overlap = (clone.x + clone.width/2.0) - (platform.x - platform.width/2.0);
-                            let right_overlap = (platform.x + platform.width/2.0) - (clone.x - clone.width/2.0);
-                            let bottom_overlap = (clone.y + clone.height/2.0) - (platform.y - platform.height/2.0);
-                            let top_overlap = (platform.y + platform.height/2.0) - (clone.y - clone.height/2.0);
-                            //find the smallest overlap
-                            let smallest_overlap = left_overlap.min(right_overlap).min(top_overlap).min(bottom_overlap);
-                            //apply the smallest overlap
-                            if smallest_overlap == left_overlap {
-                                clone.data_num[0] = clone.data_num[0].min(0.0);
-                                clone.x = platform.x - platform.width/2.0 - clone.width/2.0 + 1.0;
-                            } else if smallest_overlap == right_overlap {
-                                clone.data_num[0] = clone.data_num[0].max(0.0);
-                                clone.x = platform.x + platform.width/2.0 + clone.width/2.0 - 1.0;
-                            } else if smallest_overlap == top_overlap {
-                                clone.data_num[1] = clone.data_num[1].max(0.0);
-                                clone.y = platform.y + platform.height/2.0 + clone.height/2.0 - 1.0;
-                                clone.data_bool[2] = true;
-                            } else if smallest_overlap == bottom_overlap {
-                                clone.data_num[1] = clone.data_num[1].min(0.0);
-                                clone.y = platform.y - platform.height/2.0 - clone.height/2.0 + 1.0;
-                            }
-                        }
-                    }
+                    collide_platforms(&mut clone, &state.platforms.platforms);
                     //check for ground collisions
                     if clone.y - clone.height/2.0 < 0.0 {
                         clone.data_bool[2] = true;
@@ -3087,18 +4907,166 @@ fn main() {
                 reset: Rc::new(|player: &mut Player| {
                     player.x = 0.0;
                     player.y = 0.0;
-                    player.health = 70.0;
+                    player.health = 65.0;
                     player.data_bool = vec![true, false, false];
                     player.data_string = vec![];
                     player.data_num = vec![0.0, 0.0, 0.0];
                 }),
                 active: true,
+                image: 5,
+                units: vec![],
+                buffs: vec![],
+                invulnerable: 0.0,
+                flash: 0.0,
+                animation: "idle".to_string(),
+                animation_time: 0.0,
+                tilt: 0.0,
+                max_health: 0.0,
+                revived: false,
+                ability: Some(ability("nitro boost")),
+                weapon: Some(weapon("blaster")),
+                loop_made: 0,
+                upgrades: vec![
+                    Upgrade {
+                        name: "armour".to_string(),
+                        level: 0,
+                        max_level: 5,
+                        cost: 20,
+                        requires: "".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            player.health += 10.0*level as f64;
+                        }),
+                    },
+                    Upgrade {
+                        name: "weapons".to_string(),
+                        level: 0,
+                        max_level: 5,
+                        cost: 25,
+                        requires: "armour".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            player.damage *= 1.0 + 0.2*level as f64;
+                        }),
+                    },
+                    Upgrade {
+                        name: "nitro".to_string(),
+                        level: 0,
+                        max_level: 4,
+                        cost: 35,
+                        requires: "armour".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            player.speed += 0.25*level as f64;
+                            player.jump += 5.0*level as f64;
+                        }),
+                    },
+                ],
+                vehicle: 4,
+                artifacts: vec![],
+            },
+            //mid health, doesn't shoot, charges up while standing still and spends it on kills, clones or upgrades
+            Player {
+                damage: 1.0,
+                id: "Generator".to_string(),
+                x: 0.0,
+                y: 0.0,
+                width: 50.0,
+                height: 60.0,
+                health: 70.0,
+                speed: 1.0,
+                jump: 50.0,
+                data_bool: vec![false, false, false],
+                data_string: vec![],
+                data_num: vec![0.0, 0.0, 0.0, 1.0, 0.0, 1.0],
+                moves: KeySequence {sequence: vec![], step: 0, length: 0},
+                apply_inputs: Rc::new(|state: &mut Game| {
+                    let released = update_generator(&mut state.player, &state.platforms.platforms);
+                    //releasing the generator spends all the charge on every enemy on screen
+                    if released > 0.0 {
+                        zap_enemies(released/10.0*state.player.damage, "player", state);
+                    }
+                    //while anchored, the charge powers the clones close by
+                    if state.player.data_bool[0] && state.player.data_num[4] > 0.0 {
+                        let used = power_clones(state.player.x, state.player.y, state.player.id.clone(), state);
+                        state.player.data_num[4] = (state.player.data_num[4] - used).max(0.0);
+                    }
+                }),
+                apply_inputs_as_clone: Rc::new(|state: &mut Game, id: String| {
+                    let get = state.clones.get.clone();
+                    let mut clone = (get)(id, state);
+                    let released = update_generator(&mut clone, &state.platforms.platforms);
+                    if released > 0.0 {
+                        zap_enemies(released/10.0*clone.damage, "clone", state);
+                    }
+                    if clone.data_bool[0] && clone.data_num[4] > 0.0 {
+                        let used = power_clones(clone.x, clone.y, clone.id.clone(), state);
+                        clone.data_num[4] = (clone.data_num[4] - used).max(0.0);
+                    }
+                    let add = state.clones.add.clone();
+                    (add)(clone, state);
+                }),
+                reset: Rc::new(|player: &mut Player| {
+                    player.x = 0.0;
+                    player.y = 0.0;
+                    player.health = 70.0;
+                    player.data_bool = vec![false, false, false];
+                    player.data_string = vec![];
+                    //the last two are the charge, and the speed to go back to when the generator is released
+                    player.data_num = vec![0.0, 0.0, 0.0, 1.0, 0.0, 1.0];
+                }),
+                active: true,
                 image: 1,
->>>>>>> 0bbb193309acffbfaebdc1ab3ddabe085f9e14c0
+                units: vec![],
+                buffs: vec![],
+                invulnerable: 0.0,
+                flash: 0.0,
+                animation: "idle".to_string(),
+                animation_time: 0.0,
+                tilt: 0.0,
+                max_health: 0.0,
+                revived: false,
+                ability: Some(ability("barrier")),
+                weapon: None,
+                loop_made: 0,
+                upgrades: vec![
+                    Upgrade {
+                        name: "armour".to_string(),
+                        level: 0,
+                        max_level: 5,
+                        cost: 20,
+                        requires: "".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            player.health += 10.0*level as f64;
+                        }),
+                    },
+                    Upgrade {
+                        name: "output".to_string(),
+                        level: 0,
+                        max_level: 4,
+                        cost: 30,
+                        requires: "armour".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            player.data_num[3] = 1.0 + 0.5*level as f64;
+                        }),
+                    },
+                    Upgrade {
+                        name: "discharge".to_string(),
+                        level: 0,
+                        max_level: 3,
+                        cost: 40,
+                        requires: "output".to_string(),
+                        modify_player: Rc::new(|player: &mut Player, level: u32| {
+                            //the generator's only damage is its charge, which is scaled by damage when it is released
+                            player.damage *= 1.0 + 0.25*level as f64;
+                        }),
+                    },
+                ],
+                vehicle: 5,
+                artifacts: vec![],
             }
         ],
         keep_old: false,
+        swarms: 0,
     };
+    draw_artifacts(&mut menu);
     //don't add blank things for indexing
         //add a blank move to the player
         //game.player.moves.sequence.push(Keys {a: false, s: false, d: false, w:false, special: false, ability: false});
@@ -3136,132 +5104,216 @@ fn main() {
     let mut last_update = Instant::now();
     let update_interval = Duration::from_secs_f64(0.01); //update interval in seconds
 
-    let mut this_vehicle = 1;
+    let _this_vehicle = 1;
 
     let mut key_sequence = KeySequence {sequence: vec![], step: 0, length: 0};
 
     
     //load images
         //get the background image
-<<<<<<< HEAD
         let background = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/background2.jpeg",
+        asset_path("images/background2.jpeg"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("background image failed to load");
-=======
-            let background = Texture::from_path(
-            &mut window.create_texture_context(),
-            "assets/images/background2.jpeg",
-            Flip::None,
-            &TextureSettings::new(),
-            ).expect("background image failed to load");
->>>>>>> 0bbb193309acffbfaebdc1ab3ddabe085f9e14c0
 
 
 
         //make a list of the platform images
         let platform1 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/platform1.png",
+        asset_path("images/platform1.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("platform1 image failed to load");
 
         let platform2 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/platform2.png",
+        asset_path("images/platform2.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("platform2 image failed to load");
 
         let platform3 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/platform3.png",
+        asset_path("images/platform3.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("platform3 image failed to load");
 
-        let platform_images = vec![platform1, platform2, platform3];
+        let platform4 = Texture::from_path(
+        &mut window.create_texture_context(),
+        asset_path("images/processing/platform1.jpeg"),
+        Flip::None,
+        &TextureSettings::new(),
+        ).expect("platform4 image failed to load");
+
+        let platform5 = Texture::from_path(
+        &mut window.create_texture_context(),
+        asset_path("images/processing/platform2.jpeg"),
+        Flip::None,
+        &TextureSettings::new(),
+        ).expect("platform5 image failed to load");
+
+        let platform6 = Texture::from_path(
+        &mut window.create_texture_context(),
+        asset_path("images/processing/platform3.jpeg"),
+        Flip::None,
+        &TextureSettings::new(),
+        ).expect("platform6 image failed to load");
+
+        let platform_images = [platform1, platform2, platform3, platform4, platform5, platform6];
+
+        //the tile sets platforms are drawn with, a left cap, a middle that is repeated along the platform and a right cap each
+        let mut platform_tiles = vec![];
+        for i in 1..4 {
+            let mut tiles = vec![];
+            for part in ["left", "middle", "right"].iter() {
+                tiles.push(Texture::from_path(
+                &mut window.create_texture_context(),
+                asset_path(&format!("images/tiles/platform{}_{}.png", i, part)),
+                Flip::None,
+                &TextureSettings::new(),
+                ).expect("platform tile image failed to load"));
+            }
+            platform_tiles.push(tiles);
+        }
+
+        //make a list of the biome backgrounds
+        let sky_background = Texture::from_path(
+        &mut window.create_texture_context(),
+        asset_path("images/processing/background.jpeg"),
+        Flip::None,
+        &TextureSettings::new(),
+        ).expect("sky background image failed to load");
+
+        let depths_background = Texture::from_path(
+        &mut window.create_texture_context(),
+        asset_path("images/tiles/diving-2328703_1920 2.jpg"),
+        Flip::None,
+        &TextureSettings::new(),
+        ).expect("depths background image failed to load");
+
+        let background_images = [background, sky_background, depths_background];
 
 
 
         //make a list of the player images
         let player1 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/player1.png",
+        asset_path("images/player1.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("player1 image failed to load");
 
         let player2 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/player2.png",
+        asset_path("images/player2.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("player2 image failed to load");
 
         let player3 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/player3.png",
+        asset_path("images/player3.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("player3 image failed to load");
 
         let player4 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/player4.png",
+        asset_path("images/player4.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("player4 image failed to load");
 
         let player5 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/player5.png",
+        asset_path("images/player5.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("player5 image failed to load");
 
         let player6 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/player6.png",
+        asset_path("images/player6.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("player6 image failed to load");
 
         let player_images = vec![player1, player2, player3, player4, player5, player6];
 
+        //white versions of the players for flashing when they are hit, the fifth player doesn't have one
+        let mut player_white_images = vec![];
+        for i in [1, 2, 3, 4, 6].iter() {
+            player_white_images.push(Texture::from_path(
+            &mut window.create_texture_context(),
+            asset_path(&format!("images/processing/player{}white.png", i)),
+            Flip::None,
+            &TextureSettings::new(),
+            ).expect("player white image failed to load"));
+        }
+        //which white image goes with each player image
+        let player_white_index = [Some(0), Some(1), Some(2), Some(3), None, Some(4)];
+
+        //tilted versions of the players, used while jumping
+        //the rest of the tilted images still have their white backgrounds, so only the ones with transparency are used
+        let mut player_rot_images = vec![];
+        for path in ["images/processing/player2rot copy.png", "images/processing/player5rot.png"].iter() {
+            player_rot_images.push(Texture::from_path(
+            &mut window.create_texture_context(),
+            asset_path(path),
+            Flip::None,
+            &TextureSettings::new(),
+            ).expect("player rot image failed to load"));
+        }
+        let player_rot_index = [None, Some(0), None, None, Some(1), None];
+
+        let platform_white = Texture::from_path(
+        &mut window.create_texture_context(),
+        asset_path("images/processing/platform1white.png"),
+        Flip::None,
+        &TextureSettings::new(),
+        ).expect("platform white image failed to load");
+
 
 
         //make a list of the enemy images
         let enemy = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/enemy.png",
+        asset_path("images/enemy.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("enemy image failed to load");
 
-        let enemy_images = vec![enemy];
+        let enemy_heavy = Texture::from_path(
+        &mut window.create_texture_context(),
+        asset_path("images/processing/enemy.jpeg"),
+        Flip::None,
+        &TextureSettings::new(),
+        ).expect("enemy image failed to load");
+
+        let enemy_images = [enemy, enemy_heavy];
 
 
 
         //make a list of the bullet images
         let bullet = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/bullet.png",
+        asset_path("images/bullet.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("bullet image failed to load");
 
-        let bullet_images = vec![bullet];
+        let bullet_images = [bullet];
 
 
 
         //get the menu image
         let menu_image = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/menu.png",
+        asset_path("images/menu.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("menu image failed to load");
@@ -3271,105 +5323,105 @@ fn main() {
         //get the mouse image
         let mouse = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/cursor.png",
+        asset_path("images/cursor.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("mouse image failed to load");
         //get the button images
         let button_back = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/buttons/back.png",
+        asset_path("images/buttons/buttons/back.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button image failed to load");
 
         let button_loop = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/buttons/loop.png",
+        asset_path("images/buttons/buttons/loop.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button image failed to load");
 
         let button_play_1 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/buttons/play_1.png",
+        asset_path("images/buttons/buttons/play_1.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button image failed to load");
 
         let button_play_2 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/buttons/play_2.png",
+        asset_path("images/buttons/buttons/play_2.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button image failed to load");
 
         let button_play_3 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/buttons/play_3.png",
+        asset_path("images/buttons/buttons/play_3.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button image failed to load");
 
         let button_play_4 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/buttons/play_4.png",
+        asset_path("images/buttons/buttons/play_4.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button image failed to load");
 
         let button_play_5 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/buttons/play_5.png",
+        asset_path("images/buttons/buttons/play_5.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button image failed to load");
 
         let button_play_6 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/buttons/play_6.png",
+        asset_path("images/buttons/buttons/play_6.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button image failed to load");
 
         let button_play_7 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/buttons/play_7.png",
+        asset_path("images/buttons/buttons/play_7.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button image failed to load");
 
         let button_slack = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/buttons/slack.png",
+        asset_path("images/buttons/buttons/slack.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button image failed to load");
 
         let button_slider = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/buttons/slider.png",
+        asset_path("images/buttons/buttons/slider.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button image failed to load");
 
         let button_switch_1 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/buttons/switch_1.png",
+        asset_path("images/buttons/buttons/switch_1.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button image failed to load");
 
         let button_switch_2 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/buttons/switch_2.png",
+        asset_path("images/buttons/buttons/switch_2.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button image failed to load");
 
         let button_upgrade = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/buttons/upgrade.png",
+        asset_path("images/buttons/buttons/upgrade.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button image failed to load");
@@ -3379,84 +5431,84 @@ fn main() {
         //get the button text images
         let button_text_batte = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/text/batte.png",
+        asset_path("images/buttons/text/batte.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button text image failed to load");
 
         let button_text_bealth = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/text/bealth.png",
+        asset_path("images/buttons/text/bealth.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button text image failed to load");
 
         let button_text_damage = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/text/damage.png",
+        asset_path("images/buttons/text/damage.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button text image failed to load");
 
         let button_text_end_run_1 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/text/end_run_1.png",
+        asset_path("images/buttons/text/end_run_1.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button text image failed to load");
 
         let button_text_end_run_2 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/text/end_run_2.png",
+        asset_path("images/buttons/text/end_run_2.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button text image failed to load");
 
         let button_text_next = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/text/next.png",
+        asset_path("images/buttons/text/next.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button text image failed to load");
 
         let button_text_switch = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/text/switch.png",
+        asset_path("images/buttons/text/switch.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button text image failed to load");
 
         let button_text_upgrade_daalge = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/text/upgrade_daalge.png",
+        asset_path("images/buttons/text/upgrade_daalge.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button text image failed to load");
 
         let button_text_upgrade_damage = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/text/upgrade_damage.png",
+        asset_path("images/buttons/text/upgrade_damage.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button text image failed to load");
 
         let button_text_upgrade_health_1 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/text/upgrade_health_1.png",
+        asset_path("images/buttons/text/upgrade_health_1.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button text image failed to load");
 
         let button_text_upgrade_health_2 = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/text/upgrade_health_2.png",
+        asset_path("images/buttons/text/upgrade_health_2.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button text image failed to load");
 
         let button_text_upgrade = Texture::from_path(
         &mut window.create_texture_context(),
-        "assets/images/buttons/text/upgrade.png",
+        asset_path("images/buttons/text/upgrade.png"),
         Flip::None,
         &TextureSettings::new(),
         ).expect("button text image failed to load");
@@ -3464,33 +5516,46 @@ fn main() {
         let button_texts = vec![button_text_batte, button_text_bealth, button_text_damage, button_text_end_run_1, button_text_end_run_2, button_text_next, button_text_switch, button_text_upgrade_daalge, button_text_upgrade_damage, button_text_upgrade_health_1, button_text_upgrade_health_2, button_text_upgrade];
 
     while let Some(event) = window.next() {
-        if let Some(_) = event.update_args() {
+        if event.update_args().is_some() {
             let now = Instant::now();
             if now.duration_since(last_update) >= update_interval {
                 last_update = now;
                 if game.in_run {
                     update_players(&mut game);
                     update_bullets(&mut game);
+                    update_segments(&mut game);
                     update_enemies(&mut game);
                     check_hits(&mut game);
                     check_deaths(&mut game);
                     update_platforms(&mut game);
+                    update_pickups(&mut game);
+                    update_particles(&mut game);
+                    update_damage_numbers(&mut game);
                     update_camera(&mut game);
                     update_time_loop(&mut game);
-                    if check_death(&game.player) {
+                    if check_death(&game.player) && game.editor.testing {
+                        //go back to the editor without recording anything
+                        game.in_run = false;
+                        game.time_loop_storage = game.editor.kept_storage.clone();
+                        game.editor.testing = false;
+                        game.editor.active = true;
+                    } else if check_death(&game.player) {
                         end_run(&mut game);
+                        give_boss_rewards(&mut menu, &mut game);
+                        draw_artifacts(&mut menu);
                         menu.screen = 0;
                     }
                 } else {
-                    if in_run {
-                        let mut buttons = check_buttons(&mut menu, &game);
+                    if game.editor.active {
+                        update_editor(&mut menu, &mut game);
+                        update_menu(&mut menu, &mut game);
+                    } else if in_run {
+                        let buttons = check_buttons(&mut menu, &game);
                         for button in buttons {
-                            do_button(button, &mut menu);
+                            do_button(button, &mut menu, &mut game);
                         }
                         update_menu(&mut menu, &mut game);
-                    } else {
-
-                    }
+                    } 
                 }
                 if in_run {
                     key_sequence.sequence.push(game.pressed_keys.clone());
@@ -3565,6 +5630,22 @@ fn main() {
             }
         }
 
+        //the editor places things with the mouse, and changes tool, saves and leaves with the keyboard
+        if game.editor.active && !game.in_run {
+            if let Some(Button::Mouse(button)) = event.press_args() {
+                editor_click(button, true, &mut game);
+            }
+            if let Some(Button::Mouse(button)) = event.release_args() {
+                editor_click(button, false, &mut game);
+            }
+            if let Some(Button::Keyboard(key)) = event.press_args() {
+                editor_key(key, &mut menu, &mut game);
+            }
+            if let Some(text) = event.text_args() {
+                editor_text(&text, &mut game);
+            }
+        }
+
         //track the mouse
         if let Some(pos) = event.mouse_cursor_args() {
             game.mouse.x = pos[0];
@@ -3578,24 +5659,63 @@ fn main() {
 
             if game.in_run {
 
-                //draw the background at full size
-                let image_size = background.get_size();
-                image(&background, c.transform.scale(1440.0/(image_size.0 as f64), 900.0/(image_size.1 as f64)), g);
+                //draw the biome's background layers, each scrolling at its own rate
+                //every other tile is mirrored so the edges always line up
+                //just after a new biome is reached, it is drawn fading in over the last one
+                let (previous, biome, blend) = biome_blend(&game);
+                let mut faded = vec![(&biome, 1.0)];
+                if blend < 1.0 {
+                    faded = vec![(&previous, 1.0), (&biome, blend)];
+                }
+                for (biome, fade) in faded.iter() {
+                    for layer in biome.layers.iter() {
+                        let texture = if layer.source == "platform" {&platform_images[layer.image as usize]} else {&background_images[layer.image as usize]};
+                        let image_size = texture.get_size();
+                        let width = layer.height*(image_size.0 as f64)/(image_size.1 as f64);
+                        let scrolled = game.random_things.camera_distance*layer.rate;
+                        let mut tile = (scrolled/width).floor();
+                        let colour = [biome.tint[0]*layer.colour[0], biome.tint[1]*layer.colour[1], biome.tint[2]*layer.colour[2], biome.tint[3]*layer.colour[3]*(*fade as f32)];
+                        while tile*width - scrolled < 1440.0 {
+                            let x = tile*width - scrolled;
+                            let transform = if tile as i64 % 2 == 0 {
+                                c.transform.trans(x, layer.y).scale(width/(image_size.0 as f64), layer.height/(image_size.1 as f64))
+                            } else {
+                                c.transform.trans(x + width, layer.y).scale(-width/(image_size.0 as f64), layer.height/(image_size.1 as f64))
+                            };
+                            Image::new_color(colour).draw(texture, &c.draw_state, transform, g);
+                            tile += 1.0;
+                        }
+                    }
+                }
                 
                 //draw the platforms
                 for platform in game.platforms.platforms.iter() {
-                    let image_size = platform_images[platform.image as usize].get_size();
-                    image(&platform_images[platform.image as usize], c.transform.scale(platform.width/(image_size.0 as f64), platform.height/(image_size.1 as f64)).trans((platform.x - platform.width/2.0)/platform.width*(image_size.0 as f64), (900.0 - (platform.y + platform.height/2.0))/platform.height*(image_size.1 as f64)), g);
+                    let tiles = &platform_tiles[platform.image as usize];
+                    let sizes = [0, 1, 2].map(|i| (tiles[i].get_width() as f64, tiles[i].get_height() as f64));
+                    for (tile, source, place) in platform_slices(sizes, platform.x - platform.width/2.0, 900.0 - (platform.y + platform.height/2.0), platform.width, platform.height) {
+                        Image::new_color(platform_colour(platform)).src_rect(source).rect(place).draw(&tiles[tile], &c.draw_state, c.transform, g);
+                    }
+                    //crumbling platforms flicker white just before they go
+                    if platform.kind == "crumbling" && platform.data_num[1] == 1.0 && platform.data_num[0] < 0.3 && (platform.data_num[0]*20.0) as i64 % 2 == 0 {
+                        image(&platform_white, c.transform.scale(platform.width/(platform_white.get_size().0 as f64), platform.height/(platform_white.get_size().1 as f64)).trans((platform.x - platform.width/2.0)/platform.width*(platform_white.get_size().0 as f64), (900.0 - (platform.y + platform.height/2.0))/platform.height*(platform_white.get_size().1 as f64)), g);
+                    }
                 }
 
                 //draw the player
-                let image_size = player_images[game.player.image as usize].get_size();
-                image(&player_images[game.player.image as usize], c.transform.scale(game.player.width/(image_size.0 as f64), game.player.height/(image_size.1 as f64)).trans((game.player.x - game.player.width/2.0)/game.player.width*(image_size.0 as f64), (900.0 - (game.player.y + game.player.height/2.0))/game.player.height*(image_size.1 as f64)), g);
+                draw_vehicle(&game.player, &player_images, &player_rot_images, &player_rot_index, &c, g);
+
+                //flash the player white after a hit
+                if game.player.flash > 0.0 {
+                    draw_flash(&game.player, &player_white_images, &player_white_index, &c, g);
+                }
 
                 //draw the clones
-                for clone in game.clones.players.iter() {
-                    let image_size = player_images[clone.image as usize].get_size();
-                    image(&player_images[clone.image as usize], c.transform.scale(game.player.width/(image_size.0 as f64), game.player.height/(image_size.1 as f64)).trans((clone.x - clone.width/2.0)/clone.width*(image_size.0 as f64), (900.0 - (clone.y + clone.height/2.0))/clone.height*(image_size.1 as f64)), g);
+                for clone in game.clones.players.iter().filter(|clone| clone.active) {
+                    draw_vehicle(clone, &player_images, &player_rot_images, &player_rot_index, &c, g);
+                }
+
+                for clone in game.clones.players.iter().filter(|clone| clone.active && clone.flash > 0.0) {
+                    draw_flash(clone, &player_white_images, &player_white_index, &c, g);
                 }
 
                 //draw the player bullets
@@ -3610,10 +5730,128 @@ fn main() {
                     image(&bullet_images[bullet.image as usize], c.transform.scale(bullet.width/(image_size.0 as f64), bullet.height/(image_size.1 as f64)).trans((bullet.x - bullet.width/2.0)/bullet.width*(image_size.0 as f64), (900.0 - (bullet.y + bullet.height/2.0))/bullet.height*(image_size.1 as f64)), g);
                 }
 
+                //draw the pickups as squares in the colour of what they do
+                for pickup in game.pickups.pickups.iter() {
+                    rectangle(pickup_colour(&pickup.kind), [pickup.x - pickup.width/2.0, 900.0 - (pickup.y + pickup.height/2.0), pickup.width, pickup.height], c.transform, g);
+                }
+
                 //draw the enemies
                 for enemy in game.enemies.enemies.iter() {
                     let image_size = enemy_images[enemy.image as usize].get_size();
-                    image(&enemy_images[enemy.image as usize], c.transform.scale(enemy.width/(image_size.0 as f64), enemy.height/(image_size.1 as f64)).trans((enemy.x - enemy.width/2.0)/enemy.width*(image_size.0 as f64), (900.0 - (enemy.y + enemy.height/2.0))/enemy.height*(image_size.1 as f64)), g);
+                    Image::new_color(enemy.colour).draw(&enemy_images[enemy.image as usize], &c.draw_state, c.transform.scale(enemy.width/(image_size.0 as f64), enemy.height/(image_size.1 as f64)).trans((enemy.x - enemy.width/2.0)/enemy.width*(image_size.0 as f64), (900.0 - (enemy.y + enemy.height/2.0))/enemy.height*(image_size.1 as f64)), g);
+                }
+
+                //draw the particles, fading out as they run out of life
+                for particle in game.particles.iter() {
+                    let mut colour = particle.colour;
+                    colour[3] *= (particle.life/particle.max_life) as f32;
+                    rectangle(colour, [particle.x - particle.size/2.0, 900.0 - (particle.y + particle.size/2.0), particle.size, particle.size], c.transform, g);
+                }
+
+                //draw the damage numbers, fading out as they float up
+                for number in game.damage_numbers.iter() {
+                    let mut colour = number.colour;
+                    colour[3] *= (number.life/0.8).min(1.0) as f32;
+                    let text = (number.amount.round() as u32).max(1).to_string();
+                    for pixel in text_rectangles(&text, number.x, 900.0 - number.y, 2.5) {
+                        rectangle(colour, pixel, c.transform, g);
+                    }
+                }
+
+                //in the dark only the area around the player can be seen
+                if biome.rule == "darkness" {
+                    let x = game.player.x;
+                    let y = 900.0 - game.player.y;
+                    let r = biome.strength;
+                    let dark = [0.0, 0.0, 0.0, 0.85];
+                    rectangle(dark, [0.0, 0.0, 1440.0, (y - r).max(0.0)], c.transform, g);
+                    rectangle(dark, [0.0, y + r, 1440.0, 900.0], c.transform, g);
+                    rectangle(dark, [0.0, y - r, (x - r).max(0.0), 2.0*r], c.transform, g);
+                    rectangle(dark, [x + r, y - r, 1440.0, 2.0*r], c.transform, g);
+                }
+
+                //write the biome's name across the screen as it is reached, fading out as its background fades in
+                if blend < 1.0 {
+                    for pixel in text_rectangles(&biome.name, 720.0, 200.0, 8.0) {
+                        rectangle([1.0, 1.0, 1.0, 1.0 - blend as f32], pixel, c.transform, g);
+                    }
+                }
+
+                //draw the generator's charge above the player
+                if game.player.id == "Generator" {
+                    rectangle([1.0, 0.9, 0.0, 1.0], [game.player.x - 25.0, 900.0 - (game.player.y + game.player.height/2.0) - 15.0, game.player.data_num[4]/2.0, 6.0], c.transform, g);
+                }
+
+                //draw the player's ability charges in the bottom left, and how long the current use has left
+                if let Some(ability) = &game.player.ability {
+                    for i in 0..ability.max_charges {
+                        let colour = if i < ability.charges {[0.2, 0.9, 1.0, 1.0]} else {[0.2, 0.2, 0.2, 1.0]};
+                        rectangle(colour, [20.0 + i as f64*30.0, 860.0, 20.0, 20.0], c.transform, g);
+                    }
+                    if ability.time_left > 0.0 {
+                        rectangle([1.0, 1.0, 1.0, 1.0], [20.0, 845.0, 100.0*ability.time_left/ability.duration, 8.0], c.transform, g);
+                    }
+                }
+
+                //draw the boss health bars along the top, with a mark at each phase change
+                for (i, enemy) in game.enemies.enemies.iter().filter(|enemy| enemy.boss).enumerate() {
+                    let y = 20.0 + i as f64*35.0;
+                    rectangle([0.2, 0.2, 0.2, 1.0], [220.0, y, 1000.0, 25.0], c.transform, g);
+                    rectangle([0.8, 0.0, 0.0, 1.0], [220.0, y, 1000.0*(enemy.health/enemy.max_health).max(0.0), 25.0], c.transform, g);
+                    rectangle([1.0, 1.0, 1.0, 1.0], [220.0 + 1000.0/3.0, y, 2.0, 25.0], c.transform, g);
+                    rectangle([1.0, 1.0, 1.0, 1.0], [220.0 + 2000.0/3.0, y, 2.0, 25.0], c.transform, g);
+                }
+            } else if game.editor.active {
+                //draw the segment being edited as it will look when the left of the screen is editor.scroll into it
+                let image_size = background_images[0].get_size();
+                image(&background_images[0], c.transform.scale(1440.0/(image_size.0 as f64), 900.0/(image_size.1 as f64)), g);
+                let left = 1440.0 - game.editor.scroll;
+                //a line every platform's worth of distance, and a red one where the segment ends
+                let mut line = (game.editor.scroll/150.0).floor()*150.0;
+                while line < game.editor.scroll + 1440.0 {
+                    rectangle([1.0, 1.0, 1.0, 0.2], [left + line, 0.0, 1.0, 900.0], c.transform, g);
+                    line += 150.0;
+                }
+                rectangle([1.0, 0.0, 0.0, 0.8], [left + game.editor.segment.length, 0.0, 3.0, 900.0], c.transform, g);
+                for platform in game.editor.segment.platforms.iter() {
+                    let tiles = &platform_tiles[platform.image as usize];
+                    let sizes = [0, 1, 2].map(|i| (tiles[i].get_width() as f64, tiles[i].get_height() as f64));
+                    for (tile, source, place) in platform_slices(sizes, left + platform.x, 900.0 - (platform.y + platform.height/2.0), platform.width, platform.height) {
+                        Image::new_color(platform_colour(platform)).src_rect(source).rect(place).draw(&tiles[tile], &c.draw_state, c.transform, g);
+                    }
+                }
+                for spawn in game.editor.segment.enemies.iter() {
+                    if let Some(enemy_type) = game.enemy_types.iter().find(|enemy_type| enemy_type.name == spawn.name) {
+                        let image_size = enemy_images[enemy_type.image as usize].get_size();
+                        Image::new_color(enemy_type.colour).draw(&enemy_images[enemy_type.image as usize], &c.draw_state, c.transform.scale(enemy_type.width/(image_size.0 as f64), enemy_type.height/(image_size.1 as f64)).trans((left + spawn.at)/enemy_type.width*(image_size.0 as f64), (900.0 - (spawn.y + enemy_type.height/2.0))/enemy_type.height*(image_size.1 as f64)), g);
+                    }
+                }
+                for spawn in game.editor.segment.pickups.iter() {
+                    rectangle(pickup_colour(&spawn.name), [left + spawn.at - 12.5, 900.0 - (spawn.y + 12.5), 25.0, 25.0], c.transform, g);
+                }
+                //the platform being dragged out
+                if game.editor.drag.len() == 2 {
+                    let x = game.editor.drag[0].min(game.mouse.x);
+                    let y = game.editor.drag[1].max(900.0 - game.mouse.y);
+                    rectangle([1.0, 1.0, 1.0, 0.4], [x, 900.0 - y, (game.editor.drag[0] - game.mouse.x).abs(), (game.editor.drag[1] - (900.0 - game.mouse.y)).abs()], c.transform, g);
+                }
+                //the selected tool in the top left, in the colour of what it places
+                let tool = editor_tools(&game)[game.editor.tool].clone();
+                let colour = if let Some(kind) = tool.strip_prefix("pickup:") {
+                    pickup_colour(kind)
+                } else if tool.starts_with("enemy:") {
+                    game.enemy_types.iter().find(|enemy_type| enemy_type.name == tool[6..]).map(|enemy_type| enemy_type.colour).unwrap_or([1.0, 1.0, 1.0, 1.0])
+                } else {
+                    platform_colour(&layout_platform(0.0, 0.0, 0.0, &tool[9..]))
+                };
+                rectangle(colour, [20.0, 20.0, 60.0, 60.0], c.transform, g);
+                //the segment's name along the top, with a cursor while it is being typed, and what happened when it was last saved
+                let name = if game.editor.naming {format!("{}_", game.editor.segment.name)} else {game.editor.segment.name.clone()};
+                for pixel in text_rectangles(&name, 720.0, 20.0, 4.0) {
+                    rectangle([1.0, 1.0, 1.0, 1.0], pixel, c.transform, g);
+                }
+                for pixel in text_rectangles(&game.editor.message, 720.0, 60.0, 2.0) {
+                    rectangle([1.0, 0.9, 0.5, 1.0], pixel, c.transform, g);
                 }
             } else {
                 //draw the menu background, filling the screen
@@ -3634,6 +5872,71 @@ fn main() {
                         let width = height * aspect_ratio;
                         let y_offset = button_size;
                         image(&button_texts[button.text_image as usize], c.transform.scale(width/(image_size.0 as f64), height/(image_size.1 as f64)).trans((button.x - width/2.0)/width*(image_size.0 as f64), (900.0 - (button.y + button.height/2.0) + y_offset)/height*(image_size.1 as f64)), g);
+                    } else {
+                        //buttons without a text image get their label written on them instead
+                        for pixel in text_rectangles(&button_label(&button.action.action), button.x, 900.0 - button.y - 10.5, 3.0) {
+                            rectangle([1.0, 1.0, 1.0, 1.0], pixel, c.transform, g);
+                        }
+                    }
+                }
+                //on the artifact screen, write the name, rarity and description of each choice above its button
+                if menu.screen == 0 {
+                    for button in menu.button_screens.button_lists[0].buttons.iter() {
+                        if button.action.action != "artifact" {
+                            continue;
+                        }
+                        let i = button.action.index;
+                        if i >= menu.artifact_choices.len() {
+                            continue;
+                        }
+                        let artifact = &menu.artifact_choices[i];
+                        let (rarity, colour) = rarity_name(artifact.rarity);
+                        let top = 900.0 - (button.y + button.height/2.0) - 260.0;
+                        for pixel in text_rectangles(&rarity, button.x, top + 40.0, 3.0) {
+                            rectangle(colour, pixel, c.transform, g);
+                        }
+                        let mut pixels = text_rectangles(&artifact.name, button.x, top, 4.0);
+                        for (line, text) in wrap_text(&artifact.description, 28).iter().enumerate() {
+                            pixels.append(&mut text_rectangles(text, button.x, top + 80.0 + line as f64*20.0, 2.0));
+                        }
+                        for pixel in pixels {
+                            rectangle([1.0, 1.0, 1.0, 1.0], pixel, c.transform, g);
+                        }
+                    }
+                }
+
+                //draw the currency as a gold bar along the top, with the amount under its left end
+                rectangle([1.0, 0.8, 0.0, 1.0], [20.0, 20.0, (game.currency as f64*2.0).min(1400.0), 20.0], c.transform, g);
+                let currency = game.currency.to_string();
+                for pixel in text_rectangles(&currency, 20.0 + currency.len() as f64*9.0, 48.0, 3.0) {
+                    rectangle([1.0, 0.8, 0.0, 1.0], pixel, c.transform, g);
+                }
+                //on the upgrade screen, write the name and level of each upgrade in the vehicle's tree above its button
+                //and draw its cost under it, green if it can be bought
+                if menu.screen == 2 {
+                    for button in menu.button_screens.button_lists[2].buttons.iter() {
+                        if button.action.action != "upgrade" {
+                            continue;
+                        }
+                        let i = button.action.index;
+                        let upgrades = &menu.vehicles[game.player.vehicle as usize].upgrades;
+                        if i >= upgrades.len() {
+                            continue;
+                        }
+                        let top = 900.0 - (button.y + button.height/2.0) - 50.0;
+                        let mut pixels = text_rectangles(&upgrades[i].name, button.x, top, 3.0);
+                        pixels.append(&mut text_rectangles(&format!("level {}/{}", upgrades[i].level, upgrades[i].max_level), button.x, top + 30.0, 2.0));
+                        for pixel in pixels {
+                            rectangle([1.0, 1.0, 1.0, 1.0], pixel, c.transform, g);
+                        }
+                        //grey if it can't be bought yet or is maxed out
+                        let cost = upgrade_cost(&upgrades[i]);
+                        let colour = if !can_upgrade(upgrades, i) {[0.5, 0.5, 0.5, 1.0]} else if game.currency >= cost {[0.0, 0.8, 0.0, 1.0]} else {[0.8, 0.0, 0.0, 1.0]};
+                        rectangle(colour, [button.x - button.width/2.0, 900.0 - (button.y - button.height/2.0) + 10.0, (cost as f64*2.0).min(button.width), 10.0], c.transform, g);
+                        let text = if upgrades[i].level >= upgrades[i].max_level {"max".to_string()} else {cost.to_string()};
+                        for pixel in text_rectangles(&text, button.x, 900.0 - (button.y - button.height/2.0) + 28.0, 3.0) {
+                            rectangle(colour, pixel, c.transform, g);
+                        }
                     }
                 }
 
@@ -3653,3 +5956,206 @@ fn main() {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_artifact(name: &str, max_stacks: u32) -> Artifact {
+        Artifact {
+            name: name.to_string(),
+            description: "".to_string(),
+            modify_player: Rc::new(|_player: &mut Player| {}),
+            stacks: 1,
+            max_stacks,
+            affects_clones: false,
+            modify_clone: Rc::new(|_clone: &mut Player, _player: &Player| {}),
+            update_clones: Rc::new(|_state: &mut Game| {}),
+            rarity: 0,
+            weight: 1.0,
+            tags: vec![],
+        }
+    }
+
+    fn test_menu(artifact_pool: Vec<Artifact>) -> Menu {
+        Menu {
+            pressed_space: false,
+            go: false,
+            quit: false,
+            screen: 0,
+            selected_vehicle: 0,
+            artifact_pool,
+            artifact_choices: vec![],
+            rng: StdRng::seed_from_u64(1),
+            artifacts: vec![],
+            last_artifact: "".to_string(),
+            button_screens: ButtonListList {button_lists: vec![]},
+            vehicles: vec![],
+            keep_old: false,
+            swarms: 0,
+        }
+    }
+
+    fn test_upgrade(name: &str, level: u32, requires: &str) -> Upgrade {
+        Upgrade {name: name.to_string(), level, max_level: 3, cost: 20, requires: requires.to_string(), modify_player: Rc::new(|_player: &mut Player, _level: u32| {})}
+    }
+
+    fn test_enemy(health: f64, max_health: f64) -> Enemy {
+        Enemy {x: 0.0, y: 0.0, width: 50.0, height: 50.0, health, speed: 1.0, last_hit_by: "".to_string(), data_num: vec![], update: Rc::new(|_id: u32, _state: &mut Game| {}), id: 0, image: 0, shoot_cool_down: 0.0, colour: [1.0, 1.0, 1.0, 1.0], max_health, boss: true, patterns: vec![], drop: "".to_string()}
+    }
+
+    #[test]
+    fn draw_artifacts_offers_three_different_artifacts() {
+        let mut menu = test_menu(["a", "b", "c", "d", "e"].iter().map(|name| test_artifact(name, 1)).collect());
+        draw_artifacts(&mut menu);
+        assert_eq!(menu.artifact_choices.len(), 3);
+        for (i, choice) in menu.artifact_choices.iter().enumerate() {
+            assert!(menu.artifact_choices[i + 1..].iter().all(|other| other.name != choice.name));
+        }
+    }
+
+    #[test]
+    fn roll_artifacts_skips_only_maxed_out_artifacts() {
+        let mut menu = test_menu(vec![test_artifact("maxed", 1), test_artifact("stacking", 3)]);
+        menu.artifacts = vec![test_artifact("maxed", 1), test_artifact("stacking", 3)];
+        let picks = roll_artifacts(&mut menu, 3);
+        assert_eq!(picks.len(), 1);
+        assert_eq!(picks[0].name, "stacking");
+        //rolling doesn't touch the choices on offer
+        assert!(menu.artifact_choices.is_empty());
+    }
+
+    #[test]
+    fn upgrades_cost_more_each_level() {
+        assert_eq!(upgrade_cost(&test_upgrade("armour", 0, "")), 20);
+        assert_eq!(upgrade_cost(&test_upgrade("armour", 1, "")), 30);
+        assert_eq!(upgrade_cost(&test_upgrade("armour", 2, "")), 45);
+    }
+
+    #[test]
+    fn upgrades_need_the_one_before_them_and_stop_at_max() {
+        let mut upgrades = vec![test_upgrade("armour", 0, ""), test_upgrade("weapons", 0, "armour")];
+        assert!(can_upgrade(&upgrades, 0));
+        assert!(!can_upgrade(&upgrades, 1));
+        upgrades[0].level = 3;
+        assert!(!can_upgrade(&upgrades, 0));
+        assert!(can_upgrade(&upgrades, 1));
+    }
+
+    #[test]
+    fn abilities_use_a_charge_per_press_and_recharge() {
+        let mut dash = ability("dash");
+        assert_eq!(tick_ability(&mut dash, true), (true, true));
+        //holding the key doesn't use another charge
+        assert_eq!(tick_ability(&mut dash, true), (false, false));
+        assert_eq!(dash.charges, 2);
+        for _ in 0..300 {
+            tick_ability(&mut dash, false);
+        }
+        assert_eq!(dash.charges, 3);
+    }
+
+    #[test]
+    fn abilities_with_a_duration_end_once_it_runs_out() {
+        let mut rally = ability("rally");
+        assert_eq!(tick_ability(&mut rally, true), (true, false));
+        let mut ends = 0;
+        for _ in 0..500 {
+            if tick_ability(&mut rally, false).1 {
+                ends += 1;
+            }
+        }
+        assert_eq!(ends, 1);
+    }
+
+    #[test]
+    fn weapons_wait_for_their_fire_rate() {
+        let mut blaster = weapon("blaster");
+        assert!(weapon_ready(&mut blaster, true));
+        assert!(!weapon_ready(&mut blaster, true));
+        let mut updates = 1;
+        while !weapon_ready(&mut blaster, true) {
+            updates += 1;
+        }
+        //the cool down drops by 0.05 every update
+        assert!((updates as f64 - blaster.fire_rate/0.05).abs() <= 1.0);
+        assert!(!weapon_ready(&mut weapon("blaster"), false));
+    }
+
+    #[test]
+    fn weapons_overheat_and_cool_down_completely() {
+        let mut minigun = weapon("minigun");
+        let mut shots = 0;
+        while !minigun.overheated {
+            if weapon_ready(&mut minigun, true) {
+                shots += 1;
+            }
+        }
+        assert!(shots > 1);
+        //it won't fire again until all the heat is gone, so the next shot starts from nothing
+        while !weapon_ready(&mut minigun, true) {}
+        assert!(!minigun.overheated);
+        assert_eq!(minigun.heat, minigun.heat_per_shot);
+    }
+
+    #[test]
+    fn jump_reach_grows_with_jump_and_speed() {
+        assert_eq!(jump_reach(0.0, 1.0), (0.0, 0.0));
+        let (low, near) = jump_reach(30.0, 1.0);
+        let (high, far) = jump_reach(50.0, 1.0);
+        assert!(high > low && far > near);
+        assert_eq!(jump_reach(50.0, 2.0).1, far*2.0);
+    }
+
+    #[test]
+    fn platform_slices_cover_the_platform_without_stretching() {
+        let sizes = [(40.0, 80.0), (320.0, 80.0), (40.0, 80.0)];
+        let pieces = platform_slices(sizes, 100.0, 50.0, 500.0, 40.0);
+        assert_eq!(pieces[0], (0, [0.0, 0.0, 40.0, 80.0], [100.0, 50.0, 20.0, 40.0]));
+        assert_eq!(pieces[1], (2, [0.0, 0.0, 40.0, 80.0], [580.0, 50.0, 20.0, 40.0]));
+        let covered: f64 = pieces.iter().map(|(_, _, place)| place[2]).sum();
+        assert!((covered - 500.0).abs() < 0.001);
+        //the middle keeps the tiles' shape, only the last one is cut short
+        for (tile, source, place) in pieces[2..].iter() {
+            assert_eq!(*tile, 1);
+            assert!((source[2]/source[3] - place[2]/place[3]).abs() < 0.001);
+        }
+        //a platform too short for both caps squeezes them and has no middle
+        let pieces = platform_slices(sizes, 0.0, 0.0, 30.0, 40.0);
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(pieces[0].2[2], 15.0);
+    }
+
+    #[test]
+    fn segments_read_back_what_was_saved() {
+        let text = "platform 0 100 300 50 plain\nplatform 150 250 350 50 one way\nenemy 300 400 drifter\npickup 200 150 repair\n";
+        let segment = read_segment("test", text).unwrap();
+        assert_eq!(segment.platforms.len(), 2);
+        assert_eq!(segment.platforms[1].kind, "one way");
+        assert_eq!(segment.length, 500.0);
+        assert_eq!(segment_text(&segment), text);
+        assert_eq!(segment_text(&read_segment("test", &segment_text(&segment)).unwrap()), text);
+    }
+
+    #[test]
+    fn segments_say_which_line_cant_be_read() {
+        assert_eq!(read_segment("test", "platform 0 100 300 50 plain\nplatform 0 x 300 50 plain").err(), Some("line 2 of test: expected a number".to_string()));
+        assert_eq!(read_segment("test", "ladder 0 100").err(), Some("line 1 of test: can't read \"ladder 0 100\"".to_string()));
+    }
+
+    #[test]
+    fn bosses_change_phase_at_each_third_of_their_health() {
+        assert_eq!(boss_phase(&test_enemy(300.0, 300.0)), 0);
+        assert_eq!(boss_phase(&test_enemy(200.0, 300.0)), 1);
+        assert_eq!(boss_phase(&test_enemy(150.0, 300.0)), 1);
+        assert_eq!(boss_phase(&test_enemy(100.0, 300.0)), 2);
+        assert_eq!(boss_phase(&test_enemy(0.0, 300.0)), 2);
+    }
+
+    #[test]
+    fn wrap_text_breaks_between_words() {
+        assert_eq!(wrap_text("the quick brown fox", 10), vec!["the quick", "brown fox"]);
+        assert_eq!(wrap_text("overclocked barrel", 5), vec!["overclocked", "barrel"]);
+        assert!(wrap_text("", 10).is_empty());
+    }
+}